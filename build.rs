@@ -0,0 +1,29 @@
+fn main() {
+    // Expose the target triple to the binary for --build-info
+    println!(
+        "cargo:rustc-env=TARGET={}",
+        std::env::var("TARGET").unwrap_or_else(|_| "unknown".to_string())
+    );
+
+    // Expose the resolved polars version from Cargo.lock so it can't go stale
+    println!("cargo:rustc-env=POLARS_VERSION={}", locked_version("polars").unwrap_or_else(|| "unknown".to_string()));
+
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=Cargo.lock");
+}
+
+/// Version of a package as resolved in Cargo.lock, if the lock file is present
+fn locked_version(package: &str) -> Option<String> {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").ok()?;
+    let lock = std::fs::read_to_string(std::path::Path::new(&manifest_dir).join("Cargo.lock")).ok()?;
+
+    let name_line = format!("name = \"{}\"", package);
+    let mut lines = lock.lines();
+    while let Some(line) = lines.next() {
+        if line.trim() == name_line {
+            let version = lines.next()?.trim().strip_prefix("version = \"")?.strip_suffix('"')?;
+            return Some(version.to_string());
+        }
+    }
+    None
+}
@@ -1,12 +1,41 @@
 use polars::prelude::*;
 use rayon::prelude::*;
+use crate::context::AnalyzerContext;
+use crate::locale::NumberFormat;
 use crate::Result;
 
 /// Calculate daily returns for a price column
 pub fn calculate_returns(df: &DataFrame, price_col: &str) -> Result<DataFrame> {
     let prices = df.column(price_col)?.f64()?;
 
-    // Manually calculate percentage change
+    let returns_series = Series::new("daily_return".into(), daily_returns(prices));
+    let mut result = df.clone();
+    result.with_column(returns_series)?;
+
+    Ok(result)
+}
+
+/// Calculate daily returns for several price columns at once
+/// Appends a `{col}_return` column per input, computed in parallel
+pub fn calculate_returns_all(df: &DataFrame, price_cols: &[&str]) -> Result<DataFrame> {
+    let returns: Vec<Result<Series>> = price_cols
+        .par_iter()
+        .map(|price_col| {
+            let prices = df.column(price_col)?.f64()?;
+            Ok(Series::new(format!("{}_return", price_col).into(), daily_returns(prices)))
+        })
+        .collect();
+
+    let mut result = df.clone();
+    for returns_series in returns {
+        result.with_column(returns_series?)?;
+    }
+
+    Ok(result)
+}
+
+/// Percentage change between consecutive prices; the first value is always null
+fn daily_returns(prices: &Float64Chunked) -> Vec<Option<f64>> {
     let mut returns_vec = Vec::with_capacity(prices.len());
     returns_vec.push(None); // First value is always null
 
@@ -22,11 +51,7 @@ pub fn calculate_returns(df: &DataFrame, price_col: &str) -> Result<DataFrame> {
         }
     }
 
-    let returns_series = Series::new("daily_return".into(), returns_vec);
-    let mut result = df.clone();
-    result.with_column(returns_series)?;
-
-    Ok(result)
+    returns_vec
 }
 
 /// Calculate volatility (standard deviation of returns)
@@ -69,7 +94,31 @@ pub fn calculate_sharpe_ratio(
 /// Calculate maximum drawdown
 pub fn calculate_max_drawdown(df: &DataFrame, price_col: &str) -> Result<f64> {
     let prices = df.column(price_col)?.f64()?;
+    Ok(max_drawdown(prices))
+}
+
+/// Calculate the maximum drawdown of several price columns at once, in parallel
+/// Integer price columns are cast to floats first
+/// Returns a DataFrame with columns: ETF (the price column name), Max_Drawdown, in input order
+pub fn max_drawdown_all(df: &DataFrame, price_cols: &[&str]) -> Result<DataFrame> {
+    let drawdowns: Vec<Result<f64>> = price_cols
+        .par_iter()
+        .map(|price_col| {
+            let prices = df.column(price_col)?.cast(&DataType::Float64)?;
+            Ok(max_drawdown(prices.f64()?))
+        })
+        .collect();
+
+    let result = df! {
+        "ETF" => price_cols,
+        "Max_Drawdown" => drawdowns.into_iter().collect::<Result<Vec<f64>>>()?
+    }?;
+
+    Ok(result)
+}
 
+/// Largest peak-to-trough decline as a fraction of the peak; nulls are skipped
+fn max_drawdown(prices: &Float64Chunked) -> f64 {
     let mut peak = f64::MIN;
     let mut max_dd = 0.0;
 
@@ -83,7 +132,7 @@ pub fn calculate_max_drawdown(df: &DataFrame, price_col: &str) -> Result<f64> {
         }
     }
 
-    Ok(max_dd)
+    max_dd
 }
 
 /// Compare multiple ETFs using parallel processing
@@ -96,8 +145,61 @@ pub fn compare_etfs(dfs: Vec<DataFrame>, metric_fn: fn(&DataFrame) -> Result<f64
     results.into_iter().collect()
 }
 
+/// Match an uppercased ETF symbol against an uppercased pattern
+/// `*` matches any run of characters (e.g. "IS*", "*GROWTH", "V*G"); patterns without `*` must match exactly
+fn etf_pattern_matches(pattern: &str, symbol: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or("");
+    let Some(mut rest) = symbol.strip_prefix(first) else {
+        return false;
+    };
+
+    let mut parts: Vec<&str> = parts.collect();
+    let Some(last) = parts.pop() else {
+        // No `*` in the pattern
+        return rest.is_empty();
+    };
+
+    for part in parts {
+        match rest.find(part) {
+            Some(pos) => rest = &rest[pos + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+/// Replace `*` patterns in an --etfs list with the ETFs in `df` they match (sorted), keeping list order
+/// Plain symbols are kept as given; an ETF matched by several entries is listed once
+pub fn expand_etf_patterns(df: &DataFrame, etf_symbols: &[String]) -> Result<Vec<String>> {
+    let available = get_etf_list(df)?;
+    let mut expanded: Vec<String> = Vec::new();
+
+    for symbol in etf_symbols {
+        let matches = if symbol.contains('*') {
+            let pattern = symbol.to_uppercase();
+            available
+                .iter()
+                .filter(|etf| etf_pattern_matches(&pattern, &etf.to_uppercase()))
+                .cloned()
+                .collect()
+        } else {
+            vec![symbol.clone()]
+        };
+
+        for etf in matches {
+            if !expanded.iter().any(|e| e.eq_ignore_ascii_case(&etf)) {
+                expanded.push(etf);
+            }
+        }
+    }
+
+    Ok(expanded)
+}
+
 /// Filter DataFrame to only include specified ETFs
 /// Returns filtered DataFrame containing only rows where ETF column matches one of the specified ETF symbols
+/// Symbols may contain `*` wildcards, e.g. "IWF,IS*" selects IWF and every ETF starting with IS
 pub fn filter_etfs(df: &DataFrame, etf_symbols: &[String]) -> Result<DataFrame> {
     if etf_symbols.is_empty() {
         return Ok(df.clone());
@@ -122,7 +224,7 @@ pub fn filter_etfs(df: &DataFrame, etf_symbols: &[String]) -> Result<DataFrame>
         let matches = etf_str
             .into_iter()
             .map(|opt_str| {
-                opt_str.map_or(false, |s| s.to_uppercase() == *etf_symbol)
+                opt_str.map_or(false, |s| etf_pattern_matches(etf_symbol, &s.to_uppercase()))
             });
         let current_mask = BooleanChunked::from_iter(matches);
         mask = mask | current_mask;
@@ -133,6 +235,267 @@ pub fn filter_etfs(df: &DataFrame, etf_symbols: &[String]) -> Result<DataFrame>
     Ok(filtered)
 }
 
+/// Drop ETFs with fewer than `min` holdings (e.g. truncated downloads)
+/// Returns the filtered DataFrame and the sorted names of the dropped ETFs
+pub fn filter_min_holdings(df: &DataFrame, min: usize) -> Result<(DataFrame, Vec<String>)> {
+    use std::collections::HashMap;
+
+    let etf_str = df.column("ETF")?.str()?;
+
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for etf in etf_str.into_iter().flatten() {
+        *counts.entry(etf).or_insert(0) += 1;
+    }
+
+    let mut dropped: Vec<String> = counts
+        .iter()
+        .filter(|(_, &count)| count < min)
+        .map(|(etf, _)| etf.to_string())
+        .collect();
+    dropped.sort();
+
+    if dropped.is_empty() {
+        return Ok((df.clone(), dropped));
+    }
+
+    let mask = BooleanChunked::from_iter(
+        etf_str
+            .into_iter()
+            .map(|opt_str| {
+                opt_str.is_some_and(|s| counts.get(s).is_some_and(|&count| count >= min))
+            })
+    );
+
+    let filtered = df.filter(&mask)?;
+
+    Ok((filtered, dropped))
+}
+
+/// How multiple search terms are combined
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum QueryMatch {
+    #[default]
+    Any,  // Row matches if it contains at least one term
+    All,  // Row matches only if it contains every term
+}
+
+impl QueryMatch {
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "all" | "and" => QueryMatch::All,
+            _ => QueryMatch::Any, // Default
+        }
+    }
+}
+
+/// Find holdings whose Symbol or Name contains the search terms (case-insensitive)
+/// Terms are combined with OR (QueryMatch::Any) or AND (QueryMatch::All)
+pub fn search_holdings(df: &DataFrame, queries: &[String], match_mode: QueryMatch) -> Result<DataFrame> {
+    let terms: Vec<String> = queries
+        .iter()
+        .map(|q| q.trim().to_lowercase())
+        .filter(|q| !q.is_empty())
+        .collect();
+
+    if terms.is_empty() {
+        return Err(crate::Error::Other("Search requires at least one non-empty query".to_string()));
+    }
+
+    let symbols = df.column("Symbol")?.str()?;
+    let names = df.column("Name")?.str()?;
+
+    // Build one mask per term, then combine them
+    let mut combined: Option<BooleanChunked> = None;
+    for term in &terms {
+        let mask: BooleanChunked = symbols
+            .into_iter()
+            .zip(names)
+            .map(|(symbol, name)| {
+                symbol.is_some_and(|s| s.to_lowercase().contains(term.as_str()))
+                    || name.is_some_and(|n| n.to_lowercase().contains(term.as_str()))
+            })
+            .collect();
+
+        combined = Some(match (combined, match_mode) {
+            (None, _) => mask,
+            (Some(acc), QueryMatch::Any) => acc | mask,
+            (Some(acc), QueryMatch::All) => acc & mask,
+        });
+    }
+
+    let filtered = df.filter(&combined.unwrap())?;
+
+    Ok(filtered)
+}
+
+/// Weight sums further than this from 1.0 are reported as a coverage warning
+pub const COVERAGE_TOLERANCE: f64 = 0.01;
+
+/// Category of a non-fatal data-quality issue
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WarningKind {
+    SynthesizedSymbols,  // Rows without a ticker that got an {ETF}-{No.} symbol
+    NullWeights,         // Weight cells that are missing or unparseable
+    Coverage,            // Parsed weights that don't sum to ~100%
+}
+
+impl WarningKind {
+    /// Stable identifier used in JSON output
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WarningKind::SynthesizedSymbols => "synthesized_symbols",
+            WarningKind::NullWeights => "null_weights",
+            WarningKind::Coverage => "coverage",
+        }
+    }
+}
+
+/// A non-fatal data-quality issue, optionally tied to one ETF
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataWarning {
+    pub kind: WarningKind,
+    pub message: String,
+    pub etf: Option<String>,
+}
+
+/// Collect data-quality warnings for each ETF: synthesized symbols, null weights and
+/// weight coverage outside 1.0 +/- COVERAGE_TOLERANCE. Weight checks are skipped without a Weight column
+pub fn data_quality_warnings(df: &DataFrame) -> Result<Vec<DataWarning>> {
+    use std::collections::BTreeMap;
+
+    let etf_values = df.column("ETF")?.str()?;
+    let symbols = df.column("Symbol")?.str()?;
+    let weights = if df.column("Weight").is_ok() {
+        Some(weight_values(df)?)
+    } else {
+        None
+    };
+
+    // Per ETF: (synthesized symbols, null weights, weight sum)
+    let mut by_etf: BTreeMap<&str, (usize, usize, f64)> = BTreeMap::new();
+    for (i, (etf, symbol)) in etf_values.into_iter().zip(symbols).enumerate() {
+        let Some(etf) = etf else {
+            continue;
+        };
+        let entry = by_etf.entry(etf).or_insert((0, 0, 0.0));
+        if symbol.is_some_and(|s| is_synthetic_symbol(s, etf)) {
+            entry.0 += 1;
+        }
+        if let Some(weights) = &weights {
+            match weights[i] {
+                Some(weight) => entry.2 += weight,
+                None => entry.1 += 1,
+            }
+        }
+    }
+
+    let mut warnings = Vec::new();
+    for (etf, (synthesized, null_weights, total)) in by_etf {
+        if synthesized > 0 {
+            warnings.push(DataWarning {
+                kind: WarningKind::SynthesizedSymbols,
+                message: format!("{} holding(s) without a symbol were given synthesized symbols", synthesized),
+                etf: Some(etf.to_string()),
+            });
+        }
+        if weights.is_none() {
+            continue;
+        }
+        if null_weights > 0 {
+            warnings.push(DataWarning {
+                kind: WarningKind::NullWeights,
+                message: format!("{} holding(s) have a missing or unparseable weight", null_weights),
+                etf: Some(etf.to_string()),
+            });
+        }
+        if (total - 1.0).abs() > COVERAGE_TOLERANCE {
+            warnings.push(DataWarning {
+                kind: WarningKind::Coverage,
+                message: format!("Weights sum to {:.2}% instead of 100%", total * 100.0),
+                etf: Some(etf.to_string()),
+            });
+        }
+    }
+
+    Ok(warnings)
+}
+
+/// Check whether a symbol was synthesized at load time as `{ETF}-{No.}`
+pub fn is_synthetic_symbol(symbol: &str, etf: &str) -> bool {
+    symbol.get(..etf.len()).is_some_and(|prefix| prefix.eq_ignore_ascii_case(etf))
+        && symbol[etf.len()..]
+            .strip_prefix('-')
+            .is_some_and(|digits| !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// ETFs whose share of synthesized symbols is above this are flagged by synthetic_ratio
+pub const SYNTHETIC_RATIO_THRESHOLD: f64 = 0.2;
+
+/// Share of each ETF's holdings whose Symbol matches `is_synthetic(symbol, etf)`
+/// (pass is_synthetic_symbol for the loader's `{ETF}-{No.}` pattern). A high ratio
+/// suggests a malformed file or a fund that mostly holds non-equity positions
+/// Returns a DataFrame with columns: ETF, Total, Synthetic, Ratio, Flagged (Ratio > threshold), sorted by ETF
+pub fn synthetic_ratio<F>(df: &DataFrame, is_synthetic: F, threshold: f64) -> Result<DataFrame>
+where
+    F: Fn(&str, &str) -> bool,
+{
+    use std::collections::BTreeMap;
+
+    let etf_values = df.column("ETF")?.str()?;
+    let symbols = df.column("Symbol")?.str()?;
+
+    // Per ETF: (total holdings, synthesized symbols)
+    let mut by_etf: BTreeMap<&str, (u32, u32)> = BTreeMap::new();
+    for (etf, symbol) in etf_values.into_iter().zip(symbols) {
+        let Some(etf) = etf else {
+            continue;
+        };
+        let entry = by_etf.entry(etf).or_insert((0, 0));
+        entry.0 += 1;
+        if symbol.is_some_and(|s| is_synthetic(s, etf)) {
+            entry.1 += 1;
+        }
+    }
+
+    let ratios: Vec<f64> = by_etf
+        .values()
+        .map(|&(total, synthetic)| synthetic as f64 / total as f64)
+        .collect();
+    let flagged: Vec<bool> = ratios.iter().map(|&ratio| ratio > threshold).collect();
+
+    let result = df! {
+        "ETF" => by_etf.keys().copied().collect::<Vec<_>>(),
+        "Total" => by_etf.values().map(|&(total, _)| total).collect::<Vec<_>>(),
+        "Synthetic" => by_etf.values().map(|&(_, synthetic)| synthetic).collect::<Vec<_>>(),
+        "Ratio" => ratios,
+        "Flagged" => flagged
+    }?;
+
+    Ok(result)
+}
+
+/// Drop rows whose Symbol was synthesized at load time (`{ETF}-{digits}`)
+/// Returns the filtered DataFrame and the number of rows removed
+pub fn exclude_synthetic_symbols(df: &DataFrame) -> Result<(DataFrame, usize)> {
+    let etf_str = df.column("ETF")?.str()?;
+    let symbol_str = df.column("Symbol")?.str()?;
+
+    let mask: BooleanChunked = etf_str
+        .into_iter()
+        .zip(symbol_str)
+        .map(|(etf, symbol)| match (etf, symbol) {
+            (Some(etf), Some(symbol)) => !is_synthetic_symbol(symbol, etf),
+            _ => true,
+        })
+        .collect();
+
+    let filtered = df.filter(&mask)?;
+    let removed = df.height() - filtered.height();
+
+    Ok((filtered, removed))
+}
+
 /// Sort order for assets aggregation
 #[derive(Debug, Clone, PartialEq)]
 pub enum AssetsSortBy {
@@ -141,11 +504,120 @@ pub enum AssetsSortBy {
 }
 
 impl AssetsSortBy {
+    pub fn from_str(s: &str) -> Self {
+        Self::try_from_str(s).unwrap_or(AssetsSortBy::Symbol) // Default
+    }
+
+    /// Parse a sort order, returning None for unrecognized values
+    pub fn try_from_str(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "symbol" | "alpha" | "alphabetical" => Some(AssetsSortBy::Symbol),
+            "count" | "etf_count" | "etfs" => Some(AssetsSortBy::EtfCount),
+            _ => None,
+        }
+    }
+}
+
+/// Order of the entries in ETF/asset list columns
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ListOrder {
+    #[default]
+    Sorted,      // Alphabetical, reproducible across runs
+    Appearance,  // Order in which entries appear in the input data
+}
+
+impl ListOrder {
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "appearance" | "input" => ListOrder::Appearance,
+            _ => ListOrder::Sorted, // Default
+        }
+    }
+}
+
+/// Separator between items of the ETFs/Assets list columns when joined for CSV
+const LIST_SEPARATOR: &str = ", ";
+
+/// Join every List(String) column (the ETFs/Assets lists of aggregate outputs) into ", "-separated strings
+/// Columns are picked by dtype, so lists renamed by --rename-out are joined too.
+/// Used when writing CSV (and Parquet without --list-as-array); other columns are left untouched
+pub fn join_list_columns(df: &DataFrame) -> Result<DataFrame> {
+    let mut result = df.clone();
+
+    for column in df.get_columns() {
+        if !matches!(column.dtype(), DataType::List(inner) if **inner == DataType::String) {
+            continue;
+        }
+        let name = column.name().as_str();
+
+        let joined: Vec<Option<String>> = column
+            .list()?
+            .into_iter()
+            .map(|items| {
+                items.map(|series| {
+                    series
+                        .str()
+                        .map(|ca| ca.into_iter().flatten().collect::<Vec<_>>().join(LIST_SEPARATOR))
+                        .unwrap_or_default()
+                })
+            })
+            .collect();
+        result.replace(name, Series::new(name.into(), joined))?;
+    }
+
+    Ok(result)
+}
+
+/// Build a List(String) column from grouped names, sorting each list unless keeping appearance order
+fn ordered_list_column(name: &str, list_col: &Column, order: ListOrder) -> Result<Series> {
+    let lists: Vec<Series> = list_col
+        .list()?
+        .into_iter()
+        .map(|opt_series| {
+            let mut values: Vec<&str> = match &opt_series {
+                Some(series) => series.str().map(|ca| ca.into_iter().flatten().collect()).unwrap_or_default(),
+                None => Vec::new(),
+            };
+            if order == ListOrder::Sorted {
+                values.sort_unstable();
+            }
+            Series::new(PlSmallStr::EMPTY, values)
+        })
+        .collect();
+
+    // An all-empty column would otherwise be typed List(Null)
+    let list_series = Series::new(name.into(), lists).cast(&DataType::List(Box::new(DataType::String)))?;
+    Ok(list_series)
+}
+
+/// How to combine an asset's parsed weights across the ETFs that hold it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WeightAgg {
+    None,   // Don't add a weight column
+    Sum,    // Total exposure across ETFs
+    Mean,   // Typical weight per ETF
+    Max,    // Largest single-ETF weight
+}
+
+impl WeightAgg {
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Self {
         match s.to_lowercase().as_str() {
-            "symbol" | "alpha" | "alphabetical" => AssetsSortBy::Symbol,
-            "count" | "etf_count" | "etfs" => AssetsSortBy::EtfCount,
-            _ => AssetsSortBy::Symbol, // Default
+            "sum" | "total" => WeightAgg::Sum,
+            "mean" | "avg" | "average" => WeightAgg::Mean,
+            "max" => WeightAgg::Max,
+            _ => WeightAgg::None, // Default
+        }
+    }
+
+    /// Name of the column added by this aggregation mode
+    pub fn column_name(&self) -> Option<&'static str> {
+        match self {
+            WeightAgg::None => None,
+            WeightAgg::Sum => Some("Weight_Sum"),
+            WeightAgg::Mean => Some("Weight_Mean"),
+            WeightAgg::Max => Some("Weight_Max"),
         }
     }
 }
@@ -153,6 +625,19 @@ impl AssetsSortBy {
 /// Aggregate assets across all ETFs
 /// Returns a DataFrame with columns: Symbol, Name, ETF_Count, ETFs
 pub fn aggregate_assets(df: &DataFrame, sort_by: AssetsSortBy) -> Result<DataFrame> {
+    aggregate_assets_with_weight_agg(df, sort_by, WeightAgg::None, ListOrder::default())
+}
+
+/// Aggregate assets across all ETFs, optionally combining their weights
+/// Returns a DataFrame with columns: Symbol, Name, ETF_Count, ETFs, plus
+/// Weight_Sum, Weight_Mean or Weight_Max (as a fraction) unless weight_agg is None
+/// ETFs is a List(String) column (joined with ", " when written as CSV); list_order controls its order
+pub fn aggregate_assets_with_weight_agg(
+    df: &DataFrame,
+    sort_by: AssetsSortBy,
+    weight_agg: WeightAgg,
+    list_order: ListOrder,
+) -> Result<DataFrame> {
     // Group by Symbol and aggregate
     let grouped = df
         .clone()
@@ -163,40 +648,26 @@ pub fn aggregate_assets(df: &DataFrame, sort_by: AssetsSortBy) -> Result<DataFra
             col("Name").first().alias("Name"),
             // Count unique ETFs
             col("ETF").n_unique().alias("ETF_Count"),
-            // Collect all unique ETF names as a list
-            col("ETF").unique().alias("ETF_List"),
+            // Collect all unique ETF names as a list, in order of appearance
+            col("ETF").unique_stable().alias("ETF_List"),
         ])
         .collect()?;
 
-    // Convert ETF_List to comma-separated string
-    let etf_list_col = grouped.column("ETF_List")?;
-    let etf_strings: Vec<String> = etf_list_col
-        .list()?
-        .into_iter()
-        .map(|opt_series| {
-            opt_series
-                .map(|series| {
-                    series
-                        .str()
-                        .map(|ca| {
-                            ca.into_iter()
-                                .flatten()
-                                .collect::<Vec<_>>()
-                                .join(", ")
-                        })
-                        .unwrap_or_default()
-                })
-                .unwrap_or_default()
-        })
-        .collect();
+    // Order each ETF_List; it is joined into a comma-separated string only when written as CSV
+    let etfs_col = ordered_list_column("ETFs", grouped.column("ETF_List")?, list_order)?;
 
-    // Create new DataFrame with the ETFs column as string
+    // Create new DataFrame with the ETFs list column
     let mut result = grouped
         .select(["Symbol", "Name", "ETF_Count"])?;
 
-    let etfs_col = Series::new("ETFs".into(), etf_strings);
     result.with_column(etfs_col)?;
 
+    // Add the aggregated weight column if requested
+    if let Some(column_name) = weight_agg.column_name() {
+        let weight_col = aggregate_weights_by_symbol(df, &result, weight_agg)?;
+        result.with_column(Series::new(column_name.into(), weight_col))?;
+    }
+
     // Sort based on the sort_by parameter
     let result = match sort_by {
         AssetsSortBy::Symbol => {
@@ -216,11 +687,58 @@ pub fn aggregate_assets(df: &DataFrame, sort_by: AssetsSortBy) -> Result<DataFra
     Ok(result)
 }
 
+/// Combine each asset's parsed weights across ETFs, aligned with the Symbol column of `assets`
+fn aggregate_weights_by_symbol(
+    df: &DataFrame,
+    assets: &DataFrame,
+    weight_agg: WeightAgg,
+) -> Result<Vec<Option<f64>>> {
+    use std::collections::HashMap;
+
+    let symbols = df.column("Symbol")?.str()?;
+    let weights = weight_values(df)?;
+
+    let mut weights_by_symbol: HashMap<&str, Vec<f64>> = HashMap::new();
+    for (symbol, weight) in symbols.into_iter().zip(weights) {
+        if let (Some(symbol), Some(weight)) = (symbol, weight) {
+            weights_by_symbol.entry(symbol).or_default().push(weight);
+        }
+    }
+
+    let asset_symbols = assets.column("Symbol")?.str()?;
+    let aggregated = asset_symbols
+        .into_iter()
+        .map(|symbol| {
+            let values = weights_by_symbol.get(symbol?)?;
+            match weight_agg {
+                WeightAgg::Sum => Some(values.iter().sum()),
+                WeightAgg::Mean => Some(values.iter().sum::<f64>() / values.len() as f64),
+                WeightAgg::Max => values.iter().copied().reduce(f64::max),
+                WeightAgg::None => None,
+            }
+        })
+        .collect();
+
+    Ok(aggregated)
+}
+
+/// Summary text returned when there are no rows to summarize (e.g. after an aggressive filter)
+const NO_DATA_SUMMARY: &str = "No data to summarize\n";
+
 /// Generate summary statistics for assets aggregation
 /// Returns a string summarizing how many assets appear in N ETFs
 pub fn summarize_assets(df: &DataFrame) -> Result<String> {
+    summarize_assets_with_format(df, &NumberFormat::default())
+}
+
+/// Summarize assets with counts formatted for a locale (see summarize_assets)
+pub fn summarize_assets_with_format(df: &DataFrame, format: &NumberFormat) -> Result<String> {
     use std::collections::BTreeMap;
 
+    if df.height() == 0 {
+        return Ok(NO_DATA_SUMMARY.to_string());
+    }
+
     let etf_count_col = df.column("ETF_Count")?.u32()?;
 
     // Count how many assets have each ETF_Count value
@@ -230,22 +748,82 @@ pub fn summarize_assets(df: &DataFrame) -> Result<String> {
     }
 
     let total_assets = df.height();
-    let mut summary = format!("Total assets: {}\n\n", total_assets);
+    let mut summary = format!("Total assets: {}\n\n", format.count(total_assets as u64));
     summary.push_str("Asset distribution by ETF count:\n");
 
     // Sort by ETF count (descending) for better readability
     for (etf_count, asset_count) in count_map.iter().rev() {
         let plural = if *asset_count == 1 { "asset" } else { "assets" };
         let etf_plural = if *etf_count == 1 { "ETF" } else { "ETFs" };
-        summary.push_str(&format!("  {} {} found in {} {}\n", asset_count, plural, etf_count, etf_plural));
+        summary.push_str(&format!(
+            "  {} {} found in {} {}\n",
+            format.count(*asset_count as u64),
+            plural,
+            format.count(u64::from(*etf_count)),
+            etf_plural
+        ));
     }
 
     Ok(summary)
 }
 
+/// List the `top` assets held by the most ETFs, ties broken by summed weight
+/// Returns a DataFrame with columns: Symbol, Name, ETF_Count, ETFs
+/// Without a Weight column, ties fall back to alphabetical order
+pub fn most_common_assets(df: &DataFrame, top: usize) -> Result<DataFrame> {
+    if df.column("Weight").is_err() {
+        let assets = aggregate_assets(df, AssetsSortBy::EtfCount)?;
+        return Ok(assets.head(Some(top)));
+    }
+
+    let assets = aggregate_assets_with_weight_agg(df, AssetsSortBy::Symbol, WeightAgg::Sum, ListOrder::default())?;
+
+    let ranked = assets
+        .sort(
+            ["ETF_Count", "Weight_Sum", "Symbol"],
+            SortMultipleOptions::default()
+                .with_order_descending_multi([true, true, false])
+                .with_nulls_last(true)
+                .with_maintain_order(true),
+        )?
+        .select(["Symbol", "Name", "ETF_Count", "ETFs"])?;
+
+    Ok(ranked.head(Some(top)))
+}
+
+/// Keep only assets held by between `min` and `max` ETFs (inclusive)
+/// Takes the output of aggregate_assets; `max` of None means no upper bound
+pub fn filter_by_etf_count(df: &DataFrame, min: u32, max: Option<u32>) -> Result<DataFrame> {
+    let mut predicate = col("ETF_Count").gt_eq(lit(min));
+    if let Some(max) = max {
+        predicate = predicate.and(col("ETF_Count").lt_eq(lit(max)));
+    }
+
+    let filtered = df.clone().lazy().filter(predicate).collect()?;
+
+    Ok(filtered)
+}
+
+/// Count how many assets appear in each number of ETFs
+/// Takes the output of aggregate_assets and returns a DataFrame with columns: ETF_Count, Asset_Count,
+/// sorted by ETF_Count descending (the same numbers summarize_assets prints)
+pub fn etf_count_distribution(df: &DataFrame) -> Result<DataFrame> {
+    let distribution = df
+        .clone()
+        .lazy()
+        .group_by([col("ETF_Count")])
+        .agg([col("ETF_Count").count().alias("Asset_Count")])
+        .sort(["ETF_Count"], SortMultipleOptions::default().with_order_descending(true))
+        .collect()?;
+
+    Ok(distribution)
+}
+
 /// Get unique assets (assets that appear in only one ETF)
 /// Returns a DataFrame with columns: Symbol, Name, Weight, ETF
 pub fn get_unique_assets(df: &DataFrame) -> Result<DataFrame> {
+    require_weight_column(df, "unique assets")?;
+
     // Group by Symbol to get ETF count
     let grouped = df
         .clone()
@@ -296,25 +874,39 @@ pub fn get_unique_assets(df: &DataFrame) -> Result<DataFrame> {
 /// Get list of unique ETF symbols from the DataFrame
 /// Returns a sorted vector of ETF symbols
 pub fn get_etf_list(df: &DataFrame) -> Result<Vec<String>> {
+    get_etf_list_with_order(df, ListOrder::Sorted)
+}
+
+/// Get list of unique ETFs, either sorted or in the order they first appear
+/// Appearance order follows the load order (e.g. the --files list)
+pub fn get_etf_list_with_order(df: &DataFrame, order: ListOrder) -> Result<Vec<String>> {
     let etf_col = df.column("ETF")?;
     let etf_str = etf_col.str()?;
 
-    let mut etf_set: std::collections::HashSet<String> = etf_str
+    let mut seen = std::collections::HashSet::new();
+    let mut etf_list: Vec<String> = etf_str
         .into_iter()
         .flatten()
+        .filter(|s| seen.insert(*s))
         .map(|s| s.to_string())
         .collect();
 
-    let mut etf_list: Vec<String> = etf_set.drain().collect();
-    etf_list.sort();
+    if order == ListOrder::Sorted {
+        etf_list.sort();
+    }
 
     Ok(etf_list)
 }
 
 /// Get ETF summary
 /// Returns a DataFrame with columns: ETF, Asset_Count, Assets
-/// Assets is a comma-separated list of all asset symbols in the ETF
+/// Assets is a List(String) of all asset symbols in the ETF (joined with ", " when written as CSV)
 pub fn get_etf_summary(df: &DataFrame) -> Result<DataFrame> {
+    get_etf_summary_with_list_order(df, ListOrder::default())
+}
+
+/// Get ETF summary with a specific order for the Assets list
+pub fn get_etf_summary_with_list_order(df: &DataFrame, list_order: ListOrder) -> Result<DataFrame> {
     // Group by ETF to get asset count and list of assets
     // Note: Using .implode() directly creates List(List(...)), so we need to flatten it
     let grouped = df
@@ -327,33 +919,13 @@ pub fn get_etf_summary(df: &DataFrame) -> Result<DataFrame> {
         ])
         .collect()?;
 
-    // Convert the list of assets to comma-separated strings (same pattern as aggregate_assets)
-    let assets_col = grouped.column("Assets_List")?;
-    let asset_strings: Vec<String> = assets_col
-        .list()?
-        .into_iter()
-        .map(|opt_series| {
-            opt_series
-                .map(|series| {
-                    series
-                        .str()
-                        .map(|ca| {
-                            ca.into_iter()
-                                .flatten()
-                                .collect::<Vec<_>>()
-                                .join(", ")
-                        })
-                        .unwrap_or_default()
-                })
-                .unwrap_or_default()
-        })
-        .collect();
+    // Order the list of assets (same pattern as aggregate_assets)
+    let assets_series = ordered_list_column("Assets", grouped.column("Assets_List")?, list_order)?;
 
     // Create result DataFrame without the Assets_List column
     let mut result = grouped.select(["ETF", "Asset_Count"])?;
 
-    // Add the Assets column with comma-separated strings
-    let assets_series = Series::new("Assets".into(), asset_strings);
+    // Add the Assets list column
     result.with_column(assets_series)?;
 
     // Sort by ETF name alphabetically
@@ -365,6 +937,15 @@ pub fn get_etf_summary(df: &DataFrame) -> Result<DataFrame> {
 /// Summarize ETF statistics
 /// Returns a string with summary statistics about ETFs
 pub fn summarize_etfs(summary_df: &DataFrame) -> Result<String> {
+    summarize_etfs_with_format(summary_df, &NumberFormat::default())
+}
+
+/// Summarize ETFs with counts formatted for a locale (see summarize_etfs)
+pub fn summarize_etfs_with_format(summary_df: &DataFrame, format: &NumberFormat) -> Result<String> {
+    if summary_df.height() == 0 {
+        return Ok(NO_DATA_SUMMARY.to_string());
+    }
+
     let etf_count = summary_df.height();
 
     let asset_counts = summary_df.column("Asset_Count")?;
@@ -376,9 +957,9 @@ pub fn summarize_etfs(summary_df: &DataFrame) -> Result<String> {
     let min_assets = counts.iter().min().copied().unwrap_or(0);
 
     let mut summary = String::new();
-    summary.push_str(&format!("Total ETFs: {}\n", etf_count));
-    summary.push_str(&format!("Largest ETF contains {} assets\n", max_assets));
-    summary.push_str(&format!("Smallest ETF contains {} assets\n", min_assets));
+    summary.push_str(&format!("Total ETFs: {}\n", format.count(etf_count as u64)));
+    summary.push_str(&format!("Largest ETF contains {} assets\n", format.count(u64::from(max_assets))));
+    summary.push_str(&format!("Smallest ETF contains {} assets\n", format.count(u64::from(min_assets))));
 
     Ok(summary)
 }
@@ -387,16 +968,34 @@ pub fn summarize_etfs(summary_df: &DataFrame) -> Result<String> {
 /// Returns a DataFrame with columns: Symbol, Name, ETF_Count, ETFs
 /// Can be sorted by symbol (alphabetical) or by ETF_Count (descending) then symbol
 pub fn get_asset_mapping(df: &DataFrame, sort_by: AssetsSortBy) -> Result<DataFrame> {
+    get_asset_mapping_with_list_order(df, sort_by, ListOrder::default())
+}
+
+/// Get asset-to-ETF mapping with a specific order for the ETFs list
+pub fn get_asset_mapping_with_list_order(
+    df: &DataFrame,
+    sort_by: AssetsSortBy,
+    list_order: ListOrder,
+) -> Result<DataFrame> {
     // This is similar to aggregate_assets but we'll keep it as a separate function
     // for clarity and potential future customization
-    aggregate_assets(df, sort_by)
+    aggregate_assets_with_weight_agg(df, sort_by, WeightAgg::None, list_order)
 }
 
 /// Get overlapping assets (assets that appear in more than one ETF)
 /// Returns a DataFrame with columns: Symbol, Name, ETF_Count, ETFs
-/// One row per asset with ETFs as a comma-separated list
+/// One row per asset with ETFs as a List(String) column
 /// Can be sorted by symbol (alphabetical) or by ETF_Count (descending) then symbol
 pub fn get_overlap_assets(df: &DataFrame, sort_by: AssetsSortBy) -> Result<DataFrame> {
+    get_overlap_assets_with_list_order(df, sort_by, ListOrder::default())
+}
+
+/// Get overlapping assets with a specific order for the ETFs list
+pub fn get_overlap_assets_with_list_order(
+    df: &DataFrame,
+    sort_by: AssetsSortBy,
+    list_order: ListOrder,
+) -> Result<DataFrame> {
     // Group by Symbol to get ETF count, Name, and list of ETFs
     let grouped = df
         .clone()
@@ -416,27 +1015,13 @@ pub fn get_overlap_assets(df: &DataFrame, sort_by: AssetsSortBy) -> Result<DataF
         .filter(col("ETF_Count").gt(lit(1u32)))
         .collect()?;
 
-    // Convert the list of ETFs to comma-separated strings
-    let etfs_col = overlap_df.column("ETFs_List")?;
-    let etf_strings: Vec<String> = etfs_col
-        .list()?
-        .into_iter()
-        .map(|opt_series| {
-            opt_series
-                .map(|series| {
-                    series.str()
-                        .map(|ca| ca.into_iter().flatten().collect::<Vec<_>>().join(", "))
-                        .unwrap_or_default()
-                })
-                .unwrap_or_default()
-        })
-        .collect();
+    // Order the list of ETFs
+    let etfs_series = ordered_list_column("ETFs", overlap_df.column("ETFs_List")?, list_order)?;
 
     // Create result DataFrame without the ETFs_List column
     let mut result = overlap_df.select(["Symbol", "Name", "ETF_Count"])?;
 
-    // Add the ETFs column with comma-separated strings
-    let etfs_series = Series::new("ETFs".into(), etf_strings);
+    // Add the ETFs list column
     result.with_column(etfs_series)?;
 
     // Sort based on the sort_by parameter
@@ -461,7 +1046,15 @@ pub fn get_overlap_assets(df: &DataFrame, sort_by: AssetsSortBy) -> Result<DataF
 /// Each ETF column contains the weight of that asset in the ETF, or "N/A" if not present
 /// etfs: List of ETF symbols to compare
 pub fn get_etf_comparison(df: &DataFrame, etfs: &[String]) -> Result<DataFrame> {
-    use std::collections::HashMap;
+    get_etf_comparison_with_min_present(df, etfs, 1)
+}
+
+/// Compare ETFs side-by-side, keeping only assets held by at least `min_present` of the compared ETFs
+/// Same columns as get_etf_comparison; `min_present` of 0 or 1 keeps every asset
+pub fn get_etf_comparison_with_min_present(df: &DataFrame, etfs: &[String], min_present: usize) -> Result<DataFrame> {
+    use std::collections::{HashMap, HashSet};
+
+    require_weight_column(df, "ETF comparison")?;
 
     // Filter the DataFrame to only include the specified ETFs
     let etf_col = df.column("ETF")?;
@@ -518,8 +1111,19 @@ pub fn get_etf_comparison(df: &DataFrame, etfs: &[String]) -> Result<DataFrame>
         }
     }
 
+    // Count how many of the compared ETFs hold each symbol
+    let mut presence: HashMap<&str, HashSet<String>> = HashMap::new();
+    for (symbol, etf) in symbols.into_iter().zip(etf_values) {
+        if let (Some(symbol), Some(etf)) = (symbol, etf) {
+            presence.entry(symbol).or_default().insert(etf.to_uppercase());
+        }
+    }
+
     // Create the result DataFrame
-    let symbols_vec: Vec<String> = unique_symbols.into_iter().collect();
+    let symbols_vec: Vec<String> = unique_symbols
+        .into_iter()
+        .filter(|symbol| presence.get(symbol.as_str()).map_or(0, HashSet::len) >= min_present)
+        .collect();
 
     // Start with Symbol column
     let mut result = df! {
@@ -539,16 +1143,1067 @@ pub fn get_etf_comparison(df: &DataFrame, etfs: &[String]) -> Result<DataFrame>
             })
             .collect();
 
-        let etf_series = Series::new(etf_upper.clone().into(), weights_for_etf);
-        result.with_column(etf_series)?;
+        let etf_series = Series::new(etf_upper.clone().into(), weights_for_etf);
+        result.with_column(etf_series)?;
+    }
+
+    Ok(result)
+}
+
+/// Parse a weight cell such as "5.25%" or "0.0525" into a fraction
+/// See WeightParser::parse
+pub fn parse_weight(s: &str) -> Option<f64> {
+    WeightParser.parse(s)
+}
+
+/// Parser for Weight cells, shared by every weight-based analysis
+/// AnalyzerContext runs it once over the loaded data
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct WeightParser;
+
+impl WeightParser {
+    /// Parse a single weight cell into a fraction
+    /// Values with a trailing percent sign are divided by 100; bare numbers are taken as-is
+    /// Whitespace around the number and the percent sign is ignored ("5 %", " 5.00 % ")
+    pub fn parse(&self, s: &str) -> Option<f64> {
+        let trimmed = s.trim();
+        if let Some(pct) = trimmed.strip_suffix('%') {
+            pct.trim().parse::<f64>().ok().map(|v| v / 100.0)
+        } else {
+            trimmed.parse::<f64>().ok()
+        }
+    }
+
+    /// Read the Weight column as fractions, accepting either string ("5%") or numeric storage
+    /// Unparseable or null cells are returned as None
+    pub fn values(&self, df: &DataFrame) -> Result<Vec<Option<f64>>> {
+        require_weight_column(df, "weight-based analysis")?;
+        let weight_col = df.column("Weight")?;
+
+        let values = match weight_col.dtype() {
+            DataType::String => weight_col
+                .str()?
+                .into_iter()
+                .map(|opt| opt.and_then(|s| self.parse(s)))
+                .collect(),
+            _ => weight_col
+                .cast(&DataType::Float64)?
+                .f64()?
+                .into_iter()
+                .collect(),
+        };
+
+        Ok(values)
+    }
+}
+
+/// Ensure the DataFrame has a Weight column before running a weight-dependent function
+/// Returns a Parse error naming `function` so missing weights are reported clearly
+pub fn require_weight_column(df: &DataFrame, function: &str) -> Result<()> {
+    if df.column("Weight").is_err() {
+        return Err(crate::Error::Parse(
+            format!("Weight column required for {} (check --weight-col)", function)
+        ));
+    }
+    Ok(())
+}
+
+/// Read the Weight column as fractions
+/// See WeightParser::values
+pub fn weight_values(df: &DataFrame) -> Result<Vec<Option<f64>>> {
+    WeightParser.values(df)
+}
+
+/// Get the weight profile of a single ETF
+/// Returns a DataFrame with columns: Rank, Symbol, Weight, Cumulative_Weight
+/// Rows are sorted by descending parsed weight; Cumulative_Weight is the running total,
+/// so the last row equals the total weight covered by the ETF's holdings
+pub fn weight_profile(df: &DataFrame, etf: &str) -> Result<DataFrame> {
+    require_weight_column(df, "weight profile")?;
+
+    let filtered = filter_etfs(df, &[etf.to_string()])?;
+
+    if filtered.height() == 0 {
+        return Err(crate::Error::Other(
+            format!("No data found for ETF: {}", etf)
+        ));
+    }
+
+    let symbols = filtered.column("Symbol")?.str()?;
+    let weights = weight_values(&filtered)?;
+
+    // Keep only holdings with a parseable weight
+    let mut holdings: Vec<(String, f64)> = symbols
+        .into_iter()
+        .zip(weights)
+        .filter_map(|(symbol, weight)| match (symbol, weight) {
+            (Some(s), Some(w)) => Some((s.to_string(), w)),
+            _ => None,
+        })
+        .collect();
+
+    // Sort by weight descending, then symbol for a stable order
+    holdings.sort_by(|a, b| {
+        b.1.partial_cmp(&a.1)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.0.cmp(&b.0))
+    });
+
+    let ranks: Vec<u32> = (1..=holdings.len() as u32).collect();
+    let symbols_vec: Vec<&str> = holdings.iter().map(|(s, _)| s.as_str()).collect();
+    let weights_vec: Vec<f64> = holdings.iter().map(|(_, w)| *w).collect();
+    let cumulative: Vec<f64> = weights_vec
+        .iter()
+        .scan(0.0, |total, w| {
+            *total += w;
+            Some(*total)
+        })
+        .collect();
+
+    let result = df! {
+        "Rank" => ranks,
+        "Symbol" => symbols_vec,
+        "Weight" => weights_vec,
+        "Cumulative_Weight" => cumulative
+    }?;
+
+    Ok(result)
+}
+
+/// Summarize a weight profile
+/// Returns a string stating how much of the fund the top N holdings make up
+pub fn summarize_weight_profile(profile_df: &DataFrame, etf: &str, top_n: usize) -> Result<String> {
+    summarize_weight_profile_with_format(profile_df, etf, top_n, &NumberFormat::default())
+}
+
+/// Summarize a weight profile with numbers formatted for a locale (see summarize_weight_profile)
+pub fn summarize_weight_profile_with_format(
+    profile_df: &DataFrame,
+    etf: &str,
+    top_n: usize,
+    format: &NumberFormat,
+) -> Result<String> {
+    if profile_df.height() == 0 {
+        return Ok(NO_DATA_SUMMARY.to_string());
+    }
+
+    let cumulative = profile_df.column("Cumulative_Weight")?.f64()?;
+    let holdings = profile_df.height();
+    let top = top_n.min(holdings);
+
+    let mut summary = format!(
+        "Weight profile for {} ({} holdings)\n",
+        etf.to_uppercase(),
+        format.count(holdings as u64)
+    );
+
+    if top > 0 {
+        let top_weight = cumulative.get(top - 1).unwrap_or(0.0);
+        let total_weight = cumulative.get(holdings - 1).unwrap_or(0.0);
+        summary.push_str(&format!("Top {} holdings make up {}\n", format.count(top as u64), format.percent(top_weight, 2)));
+        summary.push_str(&format!("Total weight covered: {}\n", format.percent(total_weight, 2)));
+    }
+
+    Ok(summary)
+}
+
+/// Calculate the weighted overlap matrix between all ETFs
+/// The overlap of two ETFs is the sum, over shared assets, of the smaller of the two weights,
+/// so identical funds overlap by their full weight and disjoint funds by 0.0
+/// Returns the sorted ETF labels and a symmetric matrix indexed in the same order
+pub fn weighted_overlap_matrix(df: &DataFrame) -> Result<(Vec<String>, Vec<Vec<f64>>)> {
+    weighted_overlap_matrix_with_context(&AnalyzerContext::from_frame(df)?)
+}
+
+/// Same as weighted_overlap_matrix, reading the weights parsed once by `ctx`
+pub fn weighted_overlap_matrix_with_context(ctx: &AnalyzerContext) -> Result<(Vec<String>, Vec<Vec<f64>>)> {
+    use std::collections::HashMap;
+
+    let df = &ctx.data;
+    let etfs = get_etf_list(df)?;
+    let n = etfs.len();
+
+    // Build one Symbol -> Weight map per ETF
+    let etf_values = df.column("ETF")?.str()?;
+    let symbols = df.column("Symbol")?.str()?;
+    let weights = ctx.weights("weight-based analysis")?;
+
+    let mut holdings: Vec<HashMap<String, f64>> = vec![HashMap::new(); n];
+    for ((etf, symbol), weight) in etf_values.into_iter().zip(symbols).zip(weights.iter().copied()) {
+        if let (Some(etf), Some(symbol), Some(weight)) = (etf, symbol, weight) {
+            if let Ok(idx) = etfs.binary_search_by(|e| e.as_str().cmp(etf)) {
+                *holdings[idx].entry(symbol.to_string()).or_insert(0.0) += weight;
+            }
+        }
+    }
+
+    // Generate all pairs (i, j) where i <= j, including the diagonal
+    let pairs: Vec<(usize, usize)> = (0..n)
+        .flat_map(|i| (i..n).map(move |j| (i, j)))
+        .collect();
+
+    // Calculate overlaps in parallel
+    let results: Vec<((usize, usize), f64)> = pairs
+        .par_iter()
+        .map(|&(i, j)| {
+            let overlap: f64 = holdings[i]
+                .iter()
+                .filter_map(|(symbol, wi)| holdings[j].get(symbol).map(|wj| wi.min(*wj)))
+                .sum();
+            ((i, j), overlap)
+        })
+        .collect();
+
+    let mut matrix = vec![vec![0.0; n]; n];
+    for ((i, j), overlap) in results {
+        matrix[i][j] = overlap;
+        matrix[j][i] = overlap;
+    }
+
+    Ok((etfs, matrix))
+}
+
+/// Calculate the pairwise Jaccard similarity of holdings between all ETFs
+/// Returns an ETF column followed by one column per ETF; weights are not used
+pub fn jaccard_matrix(df: &DataFrame) -> Result<DataFrame> {
+    use std::collections::HashSet;
+
+    let etfs = get_etf_list(df)?;
+    let n = etfs.len();
+
+    // Build one symbol set per ETF
+    let etf_values = df.column("ETF")?.str()?;
+    let symbols = df.column("Symbol")?.str()?;
+
+    let mut holdings: Vec<HashSet<String>> = vec![HashSet::new(); n];
+    for (etf, symbol) in etf_values.into_iter().zip(symbols) {
+        if let (Some(etf), Some(symbol)) = (etf, symbol) {
+            if let Ok(idx) = etfs.binary_search_by(|e| e.as_str().cmp(etf)) {
+                holdings[idx].insert(symbol.to_string());
+            }
+        }
+    }
+
+    // Generate all pairs (i, j) where i <= j, including the diagonal
+    let pairs: Vec<(usize, usize)> = (0..n)
+        .flat_map(|i| (i..n).map(move |j| (i, j)))
+        .collect();
+
+    // Calculate similarities in parallel
+    let results: Vec<((usize, usize), f64)> = pairs
+        .par_iter()
+        .map(|&(i, j)| {
+            let intersection = holdings[i].intersection(&holdings[j]).count();
+            let union = holdings[i].len() + holdings[j].len() - intersection;
+            let similarity = if union == 0 {
+                0.0
+            } else {
+                intersection as f64 / union as f64
+            };
+            ((i, j), similarity)
+        })
+        .collect();
+
+    let mut matrix = vec![vec![0.0; n]; n];
+    for ((i, j), similarity) in results {
+        matrix[i][j] = similarity;
+        matrix[j][i] = similarity;
+    }
+
+    let mut columns = vec![Column::new("ETF".into(), &etfs)];
+    for (j, etf) in etfs.iter().enumerate() {
+        let values: Vec<f64> = matrix.iter().map(|row| row[j]).collect();
+        columns.push(Column::new(etf.as_str().into(), values));
+    }
+
+    Ok(DataFrame::new(columns)?)
+}
+
+/// Calculate the average pairwise weighted overlap across all ETFs
+/// Returns the mean of the upper triangle of the weighted overlap matrix (diagonal excluded)
+pub fn average_overlap(df: &DataFrame) -> Result<f64> {
+    let (etfs, matrix) = weighted_overlap_matrix(df)?;
+    let n = etfs.len();
+
+    if n < 2 {
+        return Err(crate::Error::Other(
+            "Average overlap requires at least two ETFs".to_string()
+        ));
+    }
+
+    let upper: Vec<f64> = (0..n)
+        .flat_map(|i| ((i + 1)..n).map(move |j| (i, j)))
+        .map(|(i, j)| matrix[i][j])
+        .collect();
+
+    Ok(upper.iter().sum::<f64>() / upper.len() as f64)
+}
+
+/// List the K most-overlapping ETF pairs
+/// Returns a DataFrame with columns: ETF_A, ETF_B, Overlap (weighted overlap as a fraction)
+/// sorted by Overlap descending, then by ETF_A and ETF_B
+pub fn top_overlap_pairs(df: &DataFrame, k: usize) -> Result<DataFrame> {
+    let (etfs, matrix) = weighted_overlap_matrix(df)?;
+    let n = etfs.len();
+
+    let mut pairs: Vec<(usize, usize, f64)> = (0..n)
+        .flat_map(|i| ((i + 1)..n).map(move |j| (i, j)))
+        .map(|(i, j)| (i, j, matrix[i][j]))
+        .collect();
+
+    pairs.sort_by(|a, b| {
+        b.2.partial_cmp(&a.2)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| (a.0, a.1).cmp(&(b.0, b.1)))
+    });
+    pairs.truncate(k);
+
+    let etf_a: Vec<&str> = pairs.iter().map(|(i, _, _)| etfs[*i].as_str()).collect();
+    let etf_b: Vec<&str> = pairs.iter().map(|(_, j, _)| etfs[*j].as_str()).collect();
+    let overlap: Vec<f64> = pairs.iter().map(|(_, _, o)| *o).collect();
+
+    let result = df! {
+        "ETF_A" => etf_a,
+        "ETF_B" => etf_b,
+        "Overlap" => overlap
+    }?;
+
+    Ok(result)
+}
+
+/// Split each ETF's weight into the part held only by that ETF and the part shared with others
+/// Returns a DataFrame with columns: ETF, Unique_Weight, Shared_Weight (fractions), sorted by ETF
+/// A holding counts as shared if its symbol appears in any other ETF
+pub fn unique_exposure(df: &DataFrame) -> Result<DataFrame> {
+    unique_exposure_with_context(&AnalyzerContext::from_frame(df)?)
+}
+
+/// Same as unique_exposure, reading the weights parsed once by `ctx`
+pub fn unique_exposure_with_context(ctx: &AnalyzerContext) -> Result<DataFrame> {
+    use std::collections::{BTreeMap, HashMap, HashSet};
+
+    let df = &ctx.data;
+    let etf_values = df.column("ETF")?.str()?;
+    let symbols = df.column("Symbol")?.str()?;
+    let weights = ctx.weights("unique exposure")?;
+
+    // Which ETFs hold each symbol
+    let mut etfs_by_symbol: HashMap<&str, HashSet<&str>> = HashMap::new();
+    for (etf, symbol) in etf_values.into_iter().zip(symbols) {
+        if let (Some(etf), Some(symbol)) = (etf, symbol) {
+            etfs_by_symbol.entry(symbol).or_default().insert(etf);
+        }
+    }
+
+    // ETF -> (unique weight, shared weight)
+    let mut exposure: BTreeMap<&str, (f64, f64)> = BTreeMap::new();
+    for ((etf, symbol), weight) in etf_values.into_iter().zip(symbols).zip(weights.iter().copied()) {
+        if let (Some(etf), Some(symbol)) = (etf, symbol) {
+            let entry = exposure.entry(etf).or_insert((0.0, 0.0));
+            let weight = weight.unwrap_or(0.0);
+            let shared = etfs_by_symbol.get(symbol).is_some_and(|etfs| etfs.len() > 1);
+            if shared {
+                entry.1 += weight;
+            } else {
+                entry.0 += weight;
+            }
+        }
+    }
+
+    let etfs: Vec<&str> = exposure.keys().copied().collect();
+    let unique: Vec<f64> = exposure.values().map(|(u, _)| *u).collect();
+    let shared: Vec<f64> = exposure.values().map(|(_, s)| *s).collect();
+
+    let result = df! {
+        "ETF" => etfs,
+        "Unique_Weight" => unique,
+        "Shared_Weight" => shared
+    }?;
+
+    Ok(result)
+}
+
+/// Flag holdings whose weight is unusually large within their ETF
+/// A holding is flagged when its parsed weight exceeds mean + z_threshold * std of its ETF's weights
+/// (sample standard deviation). Returns a DataFrame with columns: ETF, Symbol, Weight, ZScore,
+/// sorted by ZScore descending
+pub fn weight_outliers(df: &DataFrame, z_threshold: f64) -> Result<DataFrame> {
+    weight_outliers_with_context(&AnalyzerContext::from_frame(df)?, z_threshold)
+}
+
+/// Same as weight_outliers, reading the weights parsed once by `ctx`
+pub fn weight_outliers_with_context(ctx: &AnalyzerContext, z_threshold: f64) -> Result<DataFrame> {
+    use std::collections::BTreeMap;
+
+    let df = &ctx.data;
+    let etf_values = df.column("ETF")?.str()?;
+    let symbols = df.column("Symbol")?.str()?;
+    let weights = ctx.weights("weight outliers")?;
+
+    // Group (symbol, weight) pairs by ETF
+    let mut by_etf: BTreeMap<&str, Vec<(&str, f64)>> = BTreeMap::new();
+    for ((etf, symbol), weight) in etf_values.into_iter().zip(symbols).zip(weights.iter().copied()) {
+        if let (Some(etf), Some(symbol), Some(weight)) = (etf, symbol, weight) {
+            by_etf.entry(etf).or_default().push((symbol, weight));
+        }
+    }
+
+    let mut outliers: Vec<(&str, &str, f64, f64)> = Vec::new();
+    for (etf, holdings) in &by_etf {
+        let n = holdings.len();
+        if n < 2 {
+            continue;
+        }
+
+        let mean = holdings.iter().map(|(_, w)| w).sum::<f64>() / n as f64;
+        let variance = holdings.iter().map(|(_, w)| (w - mean).powi(2)).sum::<f64>() / (n - 1) as f64;
+        let std_dev = variance.sqrt();
+        if std_dev == 0.0 {
+            continue;
+        }
+
+        for (symbol, weight) in holdings {
+            let z = (weight - mean) / std_dev;
+            if z > z_threshold {
+                outliers.push((etf, symbol, *weight, z));
+            }
+        }
+    }
+
+    outliers.sort_by(|a, b| b.3.partial_cmp(&a.3).unwrap_or(std::cmp::Ordering::Equal));
+
+    let result = df! {
+        "ETF" => outliers.iter().map(|o| o.0).collect::<Vec<_>>(),
+        "Symbol" => outliers.iter().map(|o| o.1).collect::<Vec<_>>(),
+        "Weight" => outliers.iter().map(|o| o.2).collect::<Vec<_>>(),
+        "ZScore" => outliers.iter().map(|o| o.3).collect::<Vec<_>>()
+    }?;
+
+    Ok(result)
+}
+
+/// Find each ETF's single largest holding by parsed weight, ties broken by Symbol
+/// Returns a DataFrame with columns: ETF, Symbol, Name, Weight (fraction), sorted by ETF
+pub fn largest_holding_per_etf(df: &DataFrame) -> Result<DataFrame> {
+    largest_holding_per_etf_with_context(&AnalyzerContext::from_frame(df)?)
+}
+
+/// Same as largest_holding_per_etf, reading the weights parsed once by `ctx`
+pub fn largest_holding_per_etf_with_context(ctx: &AnalyzerContext) -> Result<DataFrame> {
+    use std::collections::BTreeMap;
+
+    let df = &ctx.data;
+    let etf_values = df.column("ETF")?.str()?;
+    let symbols = df.column("Symbol")?.str()?;
+    let names = df.column("Name")?.str()?;
+    let weights = ctx.weights("largest holding")?;
+
+    let mut largest: BTreeMap<&str, (&str, Option<&str>, f64)> = BTreeMap::new();
+    for (((etf, symbol), name), weight) in etf_values.into_iter().zip(symbols).zip(names).zip(weights.iter().copied()) {
+        if let (Some(etf), Some(symbol), Some(weight)) = (etf, symbol, weight) {
+            let is_larger = match largest.get(etf) {
+                Some((best_symbol, _, best_weight)) => {
+                    weight > *best_weight || (weight == *best_weight && symbol < *best_symbol)
+                }
+                None => true,
+            };
+            if is_larger {
+                largest.insert(etf, (symbol, name, weight));
+            }
+        }
+    }
+
+    let result = df! {
+        "ETF" => largest.keys().copied().collect::<Vec<_>>(),
+        "Symbol" => largest.values().map(|h| h.0).collect::<Vec<_>>(),
+        "Name" => largest.values().map(|h| h.1).collect::<Vec<_>>(),
+        "Weight" => largest.values().map(|h| h.2).collect::<Vec<_>>()
+    }?;
+
+    Ok(result)
+}
+
+/// Calculate the Gini coefficient of each ETF's weight distribution
+/// 0.0 means all holdings are equally weighted; values near 1.0 mean a few holdings dominate
+/// Returns a DataFrame with columns: ETF, Gini, sorted by ETF
+pub fn gini_coefficient(df: &DataFrame) -> Result<DataFrame> {
+    gini_coefficient_with_context(&AnalyzerContext::from_frame(df)?)
+}
+
+/// Same as gini_coefficient, reading the weights parsed once by `ctx`
+pub fn gini_coefficient_with_context(ctx: &AnalyzerContext) -> Result<DataFrame> {
+    use std::collections::BTreeMap;
+
+    let df = &ctx.data;
+    let etf_values = df.column("ETF")?.str()?;
+    let weights = ctx.weights("Gini coefficient")?;
+
+    let mut by_etf: BTreeMap<&str, Vec<f64>> = BTreeMap::new();
+    for (etf, weight) in etf_values.into_iter().zip(weights.iter().copied()) {
+        if let (Some(etf), Some(weight)) = (etf, weight) {
+            by_etf.entry(etf).or_default().push(weight);
+        }
+    }
+
+    let etfs: Vec<&str> = by_etf.keys().copied().collect();
+    let gini: Vec<f64> = by_etf
+        .into_values()
+        .map(|mut values| {
+            // G = 2 * sum(i * x_i) / (n * sum(x)) - (n + 1) / n, with x sorted ascending and i from 1
+            values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+            let n = values.len() as f64;
+            let total: f64 = values.iter().sum();
+            if total == 0.0 {
+                return 0.0;
+            }
+            let weighted_sum: f64 = values
+                .iter()
+                .enumerate()
+                .map(|(i, x)| (i + 1) as f64 * x)
+                .sum();
+            2.0 * weighted_sum / (n * total) - (n + 1.0) / n
+        })
+        .collect();
+
+    let result = df! {
+        "ETF" => etfs,
+        "Gini" => gini
+    }?;
+
+    Ok(result)
+}
+
+/// Append a Rank_In_ETF column: each holding's position within its own ETF by descending parsed weight
+/// Rank 1 is the heaviest holding; equal weights are ordered by symbol.
+/// Rows without a parseable weight get a null rank. Row order is unchanged
+pub fn rank_within_etf(df: &DataFrame) -> Result<DataFrame> {
+    rank_within_etf_with_context(&AnalyzerContext::from_frame(df)?)
+}
+
+/// Same as rank_within_etf, reading the weights parsed once by `ctx`
+pub fn rank_within_etf_with_context(ctx: &AnalyzerContext) -> Result<DataFrame> {
+    use std::collections::HashMap;
+
+    let df = &ctx.data;
+    let etf_values = df.column("ETF")?.str()?;
+    let symbols = df.column("Symbol")?.str()?;
+    let weights = ctx.weights("rank within ETF")?;
+
+    // Row indices of weighted holdings, grouped by ETF
+    let mut by_etf: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (i, etf) in etf_values.into_iter().enumerate() {
+        if let (Some(etf), Some(_)) = (etf, weights[i]) {
+            by_etf.entry(etf).or_default().push(i);
+        }
+    }
+
+    let mut ranks: Vec<Option<u32>> = vec![None; df.height()];
+    for rows in by_etf.values_mut() {
+        rows.sort_by(|&a, &b| {
+            weights[b]
+                .partial_cmp(&weights[a])
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| symbols.get(a).cmp(&symbols.get(b)))
+        });
+        for (rank, &row) in rows.iter().enumerate() {
+            ranks[row] = Some(rank as u32 + 1);
+        }
+    }
+
+    let mut result = df.clone();
+    result.with_column(Series::new("Rank_In_ETF".into(), ranks))?;
+
+    Ok(result)
+}
+
+/// Weight-weighted ubiquity: how many ETFs hold each asset and its parsed weight summed across them
+/// Ranks an asset held at 8% in a few funds above one held at 0.1% in more funds.
+/// Missing or unparseable weights count as 0 but the holding still counts toward ETF_Count
+/// Returns a DataFrame with columns: Symbol, ETF_Count, Total_Weight, sorted by Total_Weight descending then Symbol
+pub fn weighted_ubiquity(df: &DataFrame) -> Result<DataFrame> {
+    weighted_ubiquity_with_context(&AnalyzerContext::from_frame(df)?)
+}
+
+/// Same as weighted_ubiquity, reading the weights parsed once by `ctx`
+pub fn weighted_ubiquity_with_context(ctx: &AnalyzerContext) -> Result<DataFrame> {
+    use std::collections::{BTreeMap, HashSet};
+
+    let df = &ctx.data;
+    let etf_values = df.column("ETF")?.str()?;
+    let symbols = df.column("Symbol")?.str()?;
+    let weights = ctx.weights("weighted ubiquity")?;
+
+    let mut by_symbol: BTreeMap<&str, (HashSet<&str>, f64)> = BTreeMap::new();
+    for ((etf, symbol), weight) in etf_values.into_iter().zip(symbols).zip(weights.iter().copied()) {
+        if let (Some(etf), Some(symbol)) = (etf, symbol) {
+            let entry = by_symbol.entry(symbol).or_insert_with(|| (HashSet::new(), 0.0));
+            entry.0.insert(etf);
+            entry.1 += weight.unwrap_or(0.0);
+        }
+    }
+
+    let mut rows: Vec<(&str, u32, f64)> = by_symbol
+        .into_iter()
+        .map(|(symbol, (etfs, total))| (symbol, etfs.len() as u32, total))
+        .collect();
+    // BTreeMap order is alphabetical, so a stable sort keeps Symbol as the tie-breaker
+    rows.sort_by(|a, b| b.2.partial_cmp(&a.2).unwrap_or(std::cmp::Ordering::Equal));
+
+    let result = df! {
+        "Symbol" => rows.iter().map(|r| r.0).collect::<Vec<_>>(),
+        "ETF_Count" => rows.iter().map(|r| r.1).collect::<Vec<_>>(),
+        "Total_Weight" => rows.iter().map(|r| r.2).collect::<Vec<_>>()
+    }?;
+
+    Ok(result)
+}
+
+/// Sum the parsed weights of each ETF as a quick sanity check (1.0 = fully weighted)
+/// Unparseable or missing weights are skipped, so a null-heavy ETF reports a partial sum
+/// Returns a DataFrame with columns: ETF, Weight_Sum, sorted by ETF
+pub fn weight_sum(df: &DataFrame) -> Result<DataFrame> {
+    weight_sum_with_context(&AnalyzerContext::from_frame(df)?)
+}
+
+/// Same as weight_sum, reading the weights parsed once by `ctx`
+pub fn weight_sum_with_context(ctx: &AnalyzerContext) -> Result<DataFrame> {
+    use std::collections::BTreeMap;
+
+    let df = &ctx.data;
+    let etf_values = df.column("ETF")?.str()?;
+    let weights = ctx.weights("weight sum")?;
+
+    let mut by_etf: BTreeMap<&str, f64> = BTreeMap::new();
+    for (etf, weight) in etf_values.into_iter().zip(weights.iter().copied()) {
+        if let Some(etf) = etf {
+            *by_etf.entry(etf).or_insert(0.0) += weight.unwrap_or(0.0);
+        }
+    }
+
+    let result = df! {
+        "ETF" => by_etf.keys().copied().collect::<Vec<_>>(),
+        "Weight_Sum" => by_etf.values().copied().collect::<Vec<_>>()
+    }?;
+
+    Ok(result)
+}
+
+/// Default holdings-count boundaries for size_categories: Micro < 25, Small < 100, Mid <= 500, Large above
+pub const DEFAULT_SIZE_EDGES: [usize; 3] = [25, 100, 500];
+
+/// Classify each ETF by its number of holdings
+/// With edges `[a, b, c]`: Micro when count < a, Small when a <= count < b,
+/// Mid when b <= count <= c and Large when count > c (see DEFAULT_SIZE_EDGES)
+/// Returns a DataFrame with columns: ETF, Holdings, Category, sorted by ETF
+pub fn size_categories(df: &DataFrame, edges: [usize; 3]) -> Result<DataFrame> {
+    use std::collections::BTreeMap;
+
+    if !(edges[0] <= edges[1] && edges[1] <= edges[2]) {
+        return Err(crate::Error::Other(format!(
+            "Size category boundaries must be ascending, got {}, {}, {}",
+            edges[0], edges[1], edges[2]
+        )));
+    }
+
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for etf in df.column("ETF")?.str()?.into_iter().flatten() {
+        *counts.entry(etf).or_insert(0) += 1;
+    }
+
+    let categories: Vec<&str> = counts
+        .values()
+        .map(|&count| {
+            if count < edges[0] {
+                "Micro"
+            } else if count < edges[1] {
+                "Small"
+            } else if count <= edges[2] {
+                "Mid"
+            } else {
+                "Large"
+            }
+        })
+        .collect();
+
+    let result = df! {
+        "ETF" => counts.keys().copied().collect::<Vec<_>>(),
+        "Holdings" => counts.values().map(|&count| count as u32).collect::<Vec<_>>(),
+        "Category" => categories
+    }?;
+
+    Ok(result)
+}
+
+/// Calculate every ETF's weighted overlap against a benchmark ETF
+/// Returns a DataFrame with columns: ETF, Overlap sorted by Overlap descending.
+/// The benchmark itself is included; its overlap is its own total weight (1.0 for full coverage)
+pub fn overlap_vs_benchmark(df: &DataFrame, benchmark: &str) -> Result<DataFrame> {
+    let (etfs, matrix) = weighted_overlap_matrix(df)?;
+
+    let benchmark_upper = benchmark.to_uppercase();
+    let benchmark_idx = etfs
+        .iter()
+        .position(|etf| etf.to_uppercase() == benchmark_upper)
+        .ok_or_else(|| crate::Error::Other(
+            format!("Benchmark ETF not found: {}", benchmark)
+        ))?;
+
+    let mut rows: Vec<(&str, f64)> = etfs
+        .iter()
+        .enumerate()
+        .map(|(i, etf)| (etf.as_str(), matrix[benchmark_idx][i]))
+        .collect();
+
+    rows.sort_by(|a, b| {
+        b.1.partial_cmp(&a.1)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.0.cmp(b.0))
+    });
+
+    let result = df! {
+        "ETF" => rows.iter().map(|(etf, _)| *etf).collect::<Vec<_>>(),
+        "Overlap" => rows.iter().map(|(_, overlap)| *overlap).collect::<Vec<_>>()
+    }?;
+
+    Ok(result)
+}
+
+/// Calculate every ETF's weighted overlap against an external reference holdings list
+/// (e.g. index constituents). The reference is treated as one extra pseudo-ETF, so the
+/// numbers match overlap_vs_benchmark. Returns a DataFrame with columns: ETF, Overlap
+/// sorted by Overlap descending
+pub fn overlap_with_external_index(df: &DataFrame, reference: &DataFrame) -> Result<DataFrame> {
+    const REFERENCE_ETF: &str = "__REFERENCE__";
+
+    require_weight_column(df, "external overlap")?;
+    require_weight_column(reference, "external overlap (reference file)")?;
+
+    // Keep only the columns the overlap needs, with Weight as text so both frames stack
+    let select = |frame: &DataFrame, etf: Option<&str>| -> Result<DataFrame> {
+        let mut selected = frame.select(["Symbol", "Weight"])?;
+        selected.replace("Weight", selected.column("Weight")?.cast(&DataType::String)?.take_materialized_series())?;
+        let etf_col = match etf {
+            Some(etf) => Column::new("ETF".into(), vec![etf; frame.height()]),
+            None => frame.column("ETF")?.clone(),
+        };
+        selected.with_column(etf_col)?;
+        Ok(selected)
+    };
+
+    let mut combined = select(df, None)?;
+    combined.vstack_mut(&select(reference, Some(REFERENCE_ETF))?)?;
+
+    let overlap = overlap_vs_benchmark(&combined, REFERENCE_ETF)?;
+    let result = overlap
+        .lazy()
+        .filter(col("ETF").neq(lit(REFERENCE_ETF)))
+        .collect()?;
+
+    Ok(result)
+}
+
+/// Break down the holdings of two ETFs by symbol set
+/// Returns (only_a, only_b, both) counts; ETF names are matched case-insensitively
+pub fn pair_breakdown(df: &DataFrame, etf_a: &str, etf_b: &str) -> Result<(usize, usize, usize)> {
+    use std::collections::HashSet;
+
+    let etf_values = df.column("ETF")?.str()?;
+    let symbols = df.column("Symbol")?.str()?;
+
+    let etf_a_upper = etf_a.to_uppercase();
+    let etf_b_upper = etf_b.to_uppercase();
+
+    let mut set_a: HashSet<&str> = HashSet::new();
+    let mut set_b: HashSet<&str> = HashSet::new();
+    for (etf, symbol) in etf_values.into_iter().zip(symbols) {
+        if let (Some(etf), Some(symbol)) = (etf, symbol) {
+            let etf_upper = etf.to_uppercase();
+            if etf_upper == etf_a_upper {
+                set_a.insert(symbol);
+            }
+            if etf_upper == etf_b_upper {
+                set_b.insert(symbol);
+            }
+        }
+    }
+
+    for (etf, set) in [(etf_a, &set_a), (etf_b, &set_b)] {
+        if set.is_empty() {
+            return Err(crate::Error::Other(format!("ETF not found: {}", etf)));
+        }
+    }
+
+    let both = set_a.intersection(&set_b).count();
+
+    Ok((set_a.len() - both, set_b.len() - both, both))
+}
+
+/// Compare the weights of two ETFs holding overlapping assets
+/// Returns a DataFrame with columns: Symbol, Weight_A, Weight_B, Diff (A - B, fractions) covering
+/// the union of both funds' holdings with 0.0 for absences, sorted by absolute Diff descending
+pub fn weight_diff(df: &DataFrame, etf_a: &str, etf_b: &str) -> Result<DataFrame> {
+    use std::collections::BTreeMap;
+
+    require_weight_column(df, "weight diff")?;
+
+    let etf_values = df.column("ETF")?.str()?;
+    let symbols = df.column("Symbol")?.str()?;
+    let weights = weight_values(df)?;
+
+    let etf_a_upper = etf_a.to_uppercase();
+    let etf_b_upper = etf_b.to_uppercase();
+
+    let mut found = (false, false);
+    let mut by_symbol: BTreeMap<&str, (f64, f64)> = BTreeMap::new();
+    for ((etf, symbol), weight) in etf_values.into_iter().zip(symbols).zip(weights) {
+        if let (Some(etf), Some(symbol)) = (etf, symbol) {
+            let etf_upper = etf.to_uppercase();
+            let weight = weight.unwrap_or(0.0);
+            if etf_upper == etf_a_upper {
+                found.0 = true;
+                by_symbol.entry(symbol).or_insert((0.0, 0.0)).0 += weight;
+            }
+            if etf_upper == etf_b_upper {
+                found.1 = true;
+                by_symbol.entry(symbol).or_insert((0.0, 0.0)).1 += weight;
+            }
+        }
+    }
+
+    for (etf, present) in [(etf_a, found.0), (etf_b, found.1)] {
+        if !present {
+            return Err(crate::Error::Other(format!("ETF not found: {}", etf)));
+        }
+    }
+
+    // BTreeMap iteration is by Symbol, so the stable sort keeps ties alphabetical
+    let mut rows: Vec<(&str, f64, f64)> = by_symbol
+        .into_iter()
+        .map(|(symbol, (a, b))| (symbol, a, b))
+        .collect();
+    rows.sort_by(|x, y| {
+        (y.1 - y.2).abs()
+            .partial_cmp(&(x.1 - x.2).abs())
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let result = df! {
+        "Symbol" => rows.iter().map(|r| r.0).collect::<Vec<_>>(),
+        "Weight_A" => rows.iter().map(|r| r.1).collect::<Vec<_>>(),
+        "Weight_B" => rows.iter().map(|r| r.2).collect::<Vec<_>>(),
+        "Diff" => rows.iter().map(|r| r.1 - r.2).collect::<Vec<_>>()
+    }?;
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_largest_holding_per_etf() {
+        let df = df! {
+            "ETF" => &["SPY", "SPY", "SPY", "QQQ", "QQQ", "QQQ"],
+            "Symbol" => &["AAPL", "MSFT", "NVDA", "NVDA", "MSFT", "AAPL"],
+            "Name" => &["Apple", "Microsoft", "Nvidia", "Nvidia", "Microsoft", "Apple"],
+            "Weight" => &["6%", "7%", "5%", "9%", "9%", "N/A"]
+        }.unwrap();
+
+        let largest = largest_holding_per_etf(&df).unwrap();
+        assert_eq!(largest.get_column_names(), vec!["ETF", "Symbol", "Name", "Weight"]);
+
+        // Sorted by ETF: QQQ (MSFT wins the 9% tie by symbol), SPY (MSFT at 7%)
+        let symbols: Vec<&str> = largest.column("Symbol").unwrap().str().unwrap()
+            .into_iter().flatten().collect();
+        assert_eq!(symbols, vec!["MSFT", "MSFT"]);
+
+        let weights = largest.column("Weight").unwrap().f64().unwrap();
+        assert!((weights.get(0).unwrap() - 0.09).abs() < 1e-9);
+        assert!((weights.get(1).unwrap() - 0.07).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_rank_within_etf() {
+        let df = df! {
+            "ETF" => &["SPY", "SPY", "SPY", "QQQ", "QQQ", "QQQ"],
+            "Symbol" => &["MSFT", "AAPL", "NVDA", "MSFT", "AAPL", "CASH"],
+            "Weight" => &[Some("6%"), Some("7%"), Some("5%"), Some("8%"), Some("9%"), None]
+        }.unwrap();
+
+        let ranked = rank_within_etf(&df).unwrap();
+        assert_eq!(ranked.width(), 4);
+
+        // AAPL is the heaviest holding in both ETFs; rows keep their input order
+        let ranks: Vec<Option<u32>> = ranked.column("Rank_In_ETF").unwrap().u32().unwrap().into_iter().collect();
+        assert_eq!(ranks, vec![Some(2), Some(1), Some(3), Some(2), Some(1), None]);
+    }
+
+    #[test]
+    fn test_weighted_ubiquity() {
+        let df = df! {
+            "ETF" => &["SPY", "QQQ", "SPY", "QQQ", "VTI"],
+            "Symbol" => &["AAPL", "AAPL", "TINY", "TINY", "TINY"],
+            "Weight" => &["8%", "9%", "0.1%", "0.1%", "0.1%"]
+        }.unwrap();
+
+        let ubiquity = weighted_ubiquity(&df).unwrap();
+        assert_eq!(ubiquity.get_column_names(), vec!["Symbol", "ETF_Count", "Total_Weight"]);
+
+        // AAPL is in fewer ETFs than TINY but carries far more weight
+        let symbols: Vec<&str> = ubiquity.column("Symbol").unwrap().str().unwrap().into_iter().flatten().collect();
+        assert_eq!(symbols, vec!["AAPL", "TINY"]);
+
+        let counts: Vec<u32> = ubiquity.column("ETF_Count").unwrap().u32().unwrap().into_iter().flatten().collect();
+        assert_eq!(counts, vec![2, 3]);
+
+        let totals = ubiquity.column("Total_Weight").unwrap().f64().unwrap();
+        assert!((totals.get(0).unwrap() - 0.17).abs() < 1e-9);
+        assert!((totals.get(1).unwrap() - 0.003).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_weight_sum() {
+        let df = df! {
+            "ETF" => &["FULL", "FULL", "FULL", "SPARSE", "SPARSE", "SPARSE"],
+            "Symbol" => &["AAPL", "MSFT", "NVDA", "AAPL", "MSFT", "NVDA"],
+            "Weight" => &[Some("50%"), Some("30%"), Some("20%"), Some("40%"), None, Some("N/A")]
+        }.unwrap();
+
+        let sums = weight_sum(&df).unwrap();
+        assert_eq!(sums.get_column_names(), vec!["ETF", "Weight_Sum"]);
+
+        let values = sums.column("Weight_Sum").unwrap().f64().unwrap();
+        assert!((values.get(0).unwrap() - 1.0).abs() < 1e-9);
+        assert!((values.get(1).unwrap() - 0.4).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_size_categories() {
+        let mut etfs = Vec::new();
+        for (etf, count) in [("TINY", 3), ("SMALL", 5), ("MID", 8), ("EDGE", 10), ("BIG", 11)] {
+            etfs.extend(std::iter::repeat_n(etf, count));
+        }
+        let symbols: Vec<String> = (0..etfs.len()).map(|i| format!("S{}", i)).collect();
+        let df = df! {
+            "ETF" => &etfs,
+            "Symbol" => &symbols
+        }.unwrap();
+
+        let sizes = size_categories(&df, [5, 8, 10]).unwrap();
+        assert_eq!(sizes.get_column_names(), vec!["ETF", "Holdings", "Category"]);
+
+        let names: Vec<&str> = sizes.column("ETF").unwrap().str().unwrap().into_iter().flatten().collect();
+        let categories: Vec<&str> = sizes.column("Category").unwrap().str().unwrap().into_iter().flatten().collect();
+        assert_eq!(names, vec!["BIG", "EDGE", "MID", "SMALL", "TINY"]);
+        assert_eq!(categories, vec!["Large", "Mid", "Mid", "Small", "Micro"]);
+
+        let default_sizes = size_categories(&df, DEFAULT_SIZE_EDGES).unwrap();
+        let categories = default_sizes.column("Category").unwrap().str().unwrap();
+        assert!(categories.into_iter().all(|c| c == Some("Micro")));
+
+        assert!(size_categories(&df, [100, 25, 500]).is_err());
+    }
+
+    #[test]
+    fn test_synthetic_ratio() {
+        let df = df! {
+            "ETF" => &["CMDTY", "CMDTY", "CMDTY", "CMDTY", "SPY", "SPY"],
+            "Symbol" => &["CMDTY-1", "CMDTY-2", "GLD", "USO", "AAPL", "MSFT"]
+        }.unwrap();
+
+        let ratios = synthetic_ratio(&df, is_synthetic_symbol, SYNTHETIC_RATIO_THRESHOLD).unwrap();
+        assert_eq!(ratios.get_column_names(), vec!["ETF", "Total", "Synthetic", "Ratio", "Flagged"]);
+
+        let values = ratios.column("Ratio").unwrap().f64().unwrap();
+        assert_eq!(values.get(0), Some(0.5));
+        assert_eq!(values.get(1), Some(0.0));
+
+        let flagged = ratios.column("Flagged").unwrap().bool().unwrap();
+        assert_eq!(flagged.get(0), Some(true));
+        assert_eq!(flagged.get(1), Some(false));
+
+        // A stricter threshold leaves the half-synthesized ETF unflagged
+        let ratios = synthetic_ratio(&df, is_synthetic_symbol, 0.5).unwrap();
+        assert_eq!(ratios.column("Flagged").unwrap().bool().unwrap().get(0), Some(false));
+    }
+
+    #[test]
+    fn test_list_columns_keep_items_with_separator() {
+        // An asset symbol containing the CSV separator stays one list item
+        let df = df! {
+            "ETF" => &["QQQ", "SPY", "SPY"],
+            "Symbol" => &["BRK", "BRK", "MSFT, CL A"],
+            "Name" => &["Berkshire", "Berkshire", "Microsoft"],
+        }.unwrap();
+
+        let summary = get_etf_summary(&df).unwrap();
+        let assets = summary.column("Assets").unwrap();
+        assert_eq!(assets.dtype(), &DataType::List(Box::new(DataType::String)));
+
+        let spy = assets.list().unwrap().get_as_series(1).unwrap();
+        let items: Vec<&str> = spy.str().unwrap().into_iter().flatten().collect();
+        assert_eq!(items, vec!["BRK", "MSFT, CL A"]);
+
+        let joined = join_list_columns(&summary).unwrap();
+        assert_eq!(joined.column("Assets").unwrap().str().unwrap().get(1), Some("BRK, MSFT, CL A"));
+        assert_eq!(joined.column("ETF").unwrap().dtype(), &DataType::String);
+    }
+
+    #[test]
+    fn test_get_etf_list_with_order() {
+        let df = df! {
+            "ETF" => &["VOO", "VOO", "QQQ", "SPY", "QQQ"],
+            "Symbol" => &["AAPL", "MSFT", "AAPL", "AAPL", "NVDA"]
+        }.unwrap();
+
+        assert_eq!(get_etf_list_with_order(&df, ListOrder::Sorted).unwrap(), vec!["QQQ", "SPY", "VOO"]);
+        assert_eq!(get_etf_list_with_order(&df, ListOrder::Appearance).unwrap(), vec!["VOO", "QQQ", "SPY"]);
     }
 
-    Ok(result)
-}
+    #[test]
+    fn test_calculate_returns_all() {
+        let df = df! {
+            "SPY" => &[100.0, 110.0, 99.0],
+            "QQQ" => &[50.0, 50.0, 55.0]
+        }.unwrap();
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let result = calculate_returns_all(&df, &["SPY", "QQQ"]).unwrap();
+        assert_eq!(result.get_column_names(), vec!["SPY", "QQQ", "SPY_return", "QQQ_return"]);
+
+        let spy = result.column("SPY_return").unwrap().f64().unwrap();
+        assert_eq!(spy.get(0), None);
+        assert!((spy.get(1).unwrap() - 0.10).abs() < 1e-9);
+        assert!((spy.get(2).unwrap() + 0.10).abs() < 1e-9);
+
+        let qqq = result.column("QQQ_return").unwrap().f64().unwrap();
+        assert!(qqq.get(1).unwrap().abs() < 1e-9);
+        assert!((qqq.get(2).unwrap() - 0.10).abs() < 1e-9);
+
+        assert!(calculate_returns_all(&df, &["MISSING"]).is_err());
+    }
+
+    #[test]
+    fn test_max_drawdown_all() {
+        let df = df! {
+            "SPY" => &[100.0, 120.0, 90.0, 110.0],
+            "QQQ" => &[50.0, 40.0, 60.0, 54.0]
+        }.unwrap();
+
+        let result = max_drawdown_all(&df, &["SPY", "QQQ"]).unwrap();
+        assert_eq!(result.get_column_names(), vec!["ETF", "Max_Drawdown"]);
+
+        let etfs: Vec<&str> = result.column("ETF").unwrap().str().unwrap().into_iter().flatten().collect();
+        assert_eq!(etfs, vec!["SPY", "QQQ"]);
+
+        // SPY: 120 -> 90 is 25%; QQQ: 50 -> 40 is 20%, deeper than 60 -> 54
+        let drawdowns = result.column("Max_Drawdown").unwrap().f64().unwrap();
+        assert!((drawdowns.get(0).unwrap() - 0.25).abs() < 1e-9);
+        assert!((drawdowns.get(1).unwrap() - 0.20).abs() < 1e-9);
+        assert_eq!(drawdowns.get(0), Some(calculate_max_drawdown(&df, "SPY").unwrap()));
+
+        assert!(max_drawdown_all(&df, &["MISSING"]).is_err());
+    }
 
     #[test]
     fn test_calculate_volatility_zero_variance() {
@@ -614,6 +2269,37 @@ mod tests {
         assert_eq!(filtered.height(), 2);
     }
 
+    #[test]
+    fn test_filter_etfs_glob() {
+        let df = df! {
+            "ETF" => &["VANG", "VANV", "SPY", "SPYG", "IVAN"],
+            "Symbol" => &["AAPL", "MSFT", "AAPL", "NVDA", "AMZN"],
+        }.unwrap();
+
+        let filtered = filter_etfs(&df, &["VAN*".to_string(), "SPY".to_string()]).unwrap();
+        let etfs: Vec<&str> = filtered.column("ETF").unwrap().str().unwrap().into_no_null_iter().collect();
+        assert_eq!(etfs, vec!["VANG", "VANV", "SPY"]);
+
+        let filtered = filter_etfs(&df, &["*g".to_string()]).unwrap();
+        assert_eq!(filtered.height(), 2);
+
+        assert!(etf_pattern_matches("V*N*V", "VANV"));
+        assert!(!etf_pattern_matches("V*N*V", "VANG"));
+        assert!(!etf_pattern_matches("SPY*G*G", "SPYG"));
+    }
+
+    #[test]
+    fn test_expand_etf_patterns() {
+        let df = df! {
+            "ETF" => &["VANG", "VANV", "SPY", "IVW"],
+            "Symbol" => &["AAPL", "MSFT", "AAPL", "NVDA"],
+        }.unwrap();
+
+        let patterns = vec!["spy".to_string(), "VAN*".to_string(), "*G".to_string()];
+        let expanded = expand_etf_patterns(&df, &patterns).unwrap();
+        assert_eq!(expanded, vec!["spy", "VANG", "VANV"]);
+    }
+
     #[test]
     fn test_filter_etfs_empty_list() {
         let df = df! {
@@ -667,6 +2353,94 @@ mod tests {
         assert_eq!(counts[2], 1); // MSFT (appears in 1 ETF)
     }
 
+    #[test]
+    fn test_aggregate_assets_weight_agg() {
+        let df = df! {
+            "ETF" => &["SPY", "QQQ", "SPY"],
+            "Symbol" => &["AAPL", "AAPL", "MSFT"],
+            "Name" => &["Apple", "Apple", "Microsoft"],
+            "Weight" => &["4%", "6%", "7%"]
+        }.unwrap();
+
+        let expected = [
+            (WeightAgg::Sum, "Weight_Sum", 0.10),
+            (WeightAgg::Mean, "Weight_Mean", 0.05),
+            (WeightAgg::Max, "Weight_Max", 0.06),
+        ];
+
+        for (mode, column, aapl_weight) in expected {
+            let assets = aggregate_assets_with_weight_agg(&df, AssetsSortBy::Symbol, mode, ListOrder::Sorted).unwrap();
+            let columns = assets.get_column_names();
+            assert_eq!(columns, vec!["Symbol", "Name", "ETF_Count", "ETFs", column]);
+
+            // Sorted by symbol: AAPL first
+            let weights = assets.column(column).unwrap().f64().unwrap();
+            assert!((weights.get(0).unwrap() - aapl_weight).abs() < 1e-9);
+            assert!((weights.get(1).unwrap() - 0.07).abs() < 1e-9);
+        }
+
+        // None adds no weight column
+        let assets = aggregate_assets_with_weight_agg(&df, AssetsSortBy::Symbol, WeightAgg::None, ListOrder::Sorted).unwrap();
+        assert_eq!(assets.width(), 4);
+    }
+
+    #[test]
+    fn test_aggregate_assets_list_order() {
+        let df = df! {
+            "ETF" => &["VTI", "QQQ", "SPY", "QQQ"],
+            "Symbol" => &["AAPL", "AAPL", "AAPL", "MSFT"],
+            "Name" => &["Apple", "Apple", "Apple", "Microsoft"],
+            "Weight" => &["5%", "6%", "7%", "8%"]
+        }.unwrap();
+
+        let sorted = aggregate_assets(&df, AssetsSortBy::Symbol).unwrap();
+        let sorted = join_list_columns(&sorted).unwrap();
+        let etfs = sorted.column("ETFs").unwrap().str().unwrap();
+        assert_eq!(etfs.get(0), Some("QQQ, SPY, VTI"));
+
+        let appearance = aggregate_assets_with_weight_agg(
+            &df, AssetsSortBy::Symbol, WeightAgg::None, ListOrder::Appearance
+        ).unwrap();
+        let appearance = join_list_columns(&appearance).unwrap();
+        let etfs = appearance.column("ETFs").unwrap().str().unwrap();
+        assert_eq!(etfs.get(0), Some("VTI, QQQ, SPY"));
+
+        let mapping = get_asset_mapping_with_list_order(&df, AssetsSortBy::Symbol, ListOrder::Appearance).unwrap();
+        let mapping = join_list_columns(&mapping).unwrap();
+        let etfs = mapping.column("ETFs").unwrap().str().unwrap();
+        assert_eq!(etfs.get(0), Some("VTI, QQQ, SPY"));
+
+        let overlap = get_overlap_assets(&df, AssetsSortBy::Symbol).unwrap();
+        let overlap = join_list_columns(&overlap).unwrap();
+        let etfs = overlap.column("ETFs").unwrap().str().unwrap();
+        assert_eq!(etfs.get(0), Some("QQQ, SPY, VTI"));
+
+        let summary = get_etf_summary(&df).unwrap();
+        let summary = join_list_columns(&summary).unwrap();
+        let assets = summary.column("Assets").unwrap().str().unwrap();
+        // Sorted by ETF: QQQ first, holding AAPL and MSFT
+        assert_eq!(assets.get(0), Some("AAPL, MSFT"));
+    }
+
+    #[test]
+    fn test_filter_by_etf_count() {
+        let df = df! {
+            "Symbol" => &["ONE", "THREE", "FIVE"],
+            "Name" => &["One", "Three", "Five"],
+            "ETF_Count" => &[1u32, 3u32, 5u32],
+            "ETFs" => &["A", "A, B, C", "A, B, C, D, E"]
+        }.unwrap();
+
+        let filtered = filter_by_etf_count(&df, 2, Some(4)).unwrap();
+        let symbols: Vec<&str> = filtered.column("Symbol").unwrap().str().unwrap()
+            .into_iter().flatten().collect();
+        assert_eq!(symbols, vec!["THREE"]);
+
+        // No upper bound
+        let filtered = filter_by_etf_count(&df, 2, None).unwrap();
+        assert_eq!(filtered.height(), 2);
+    }
+
     #[test]
     fn test_get_unique_assets() {
         let df = df! {
@@ -692,6 +2466,22 @@ mod tests {
         assert!(!symbol_vec.contains(&"AAPL")); // AAPL appears in 2 ETFs
     }
 
+    #[test]
+    fn test_get_unique_assets_requires_weight() {
+        let df = df! {
+            "ETF" => &["SPY", "QQQ"],
+            "Symbol" => &["AAPL", "MSFT"],
+            "Name" => &["Apple", "Microsoft"]
+        }.unwrap();
+
+        let err = get_unique_assets(&df).unwrap_err();
+        assert!(matches!(err, crate::Error::Parse(_)));
+        assert!(err.to_string().contains("Weight column required for unique assets"));
+
+        let err = get_etf_comparison(&df, &["SPY".to_string()]).unwrap_err();
+        assert!(err.to_string().contains("Weight column required for ETF comparison"));
+    }
+
     #[test]
     fn test_summarize_assets() {
         let df = df! {
@@ -708,6 +2498,28 @@ mod tests {
         assert!(summary.contains("1 asset found in 2 ETFs"));
     }
 
+    #[test]
+    fn test_summary_functions_handle_empty_input() {
+        let assets = df! {
+            "Symbol" => Vec::<&str>::new(),
+            "Name" => Vec::<&str>::new(),
+            "ETF_Count" => Vec::<u32>::new(),
+            "ETFs" => Vec::<&str>::new()
+        }.unwrap();
+        assert_eq!(summarize_assets(&assets).unwrap(), "No data to summarize\n");
+
+        let etfs = df! {
+            "ETF" => Vec::<&str>::new(),
+            "Asset_Count" => Vec::<u32>::new(),
+            "Assets" => Vec::<&str>::new()
+        }.unwrap();
+        assert_eq!(summarize_etfs(&etfs).unwrap(), "No data to summarize\n");
+
+        // A frame with no columns at all is handled too
+        assert_eq!(summarize_etfs(&DataFrame::empty()).unwrap(), "No data to summarize\n");
+        assert_eq!(summarize_weight_profile(&DataFrame::empty(), "SPY", 10).unwrap(), "No data to summarize\n");
+    }
+
     #[test]
     fn test_assets_sort_by_from_str() {
         assert_eq!(AssetsSortBy::from_str("symbol"), AssetsSortBy::Symbol);
@@ -717,6 +2529,7 @@ mod tests {
         assert_eq!(AssetsSortBy::from_str("etf_count"), AssetsSortBy::EtfCount);
         assert_eq!(AssetsSortBy::from_str("etfs"), AssetsSortBy::EtfCount);
         assert_eq!(AssetsSortBy::from_str("invalid"), AssetsSortBy::Symbol); // Default
+        assert_eq!(AssetsSortBy::try_from_str("invalid"), None);
     }
 
     #[test]
@@ -751,6 +2564,7 @@ mod tests {
         assert!(!symbol_vec.contains(&"MSFT")); // MSFT only appears in 1 ETF
 
         // Verify ETFs column contains comma-separated values
+        let overlap = join_list_columns(&overlap).unwrap();
         let etfs = overlap.column("ETFs").unwrap().str().unwrap();
         let etfs_vec: Vec<&str> = etfs.into_iter().flatten().collect();
         // Check that we have comma-separated ETF lists
@@ -818,6 +2632,7 @@ mod tests {
         // Verify AAPL appears in 2 ETFs
         let symbols = mapping.column("Symbol").unwrap().str().unwrap();
         let etf_counts = mapping.column("ETF_Count").unwrap().u32().unwrap();
+        let mapping = join_list_columns(&mapping).unwrap();
         let etfs = mapping.column("ETFs").unwrap().str().unwrap();
 
         let symbol_vec: Vec<&str> = symbols.into_iter().flatten().collect();
@@ -932,6 +2747,7 @@ mod tests {
         assert_eq!(counts[qqq_idx], 1);
 
         // Verify assets column contains comma-separated symbols
+        let summary = join_list_columns(&summary).unwrap();
         let assets = summary.column("Assets").unwrap().str().unwrap();
         let assets_vec: Vec<&str> = assets.into_iter().flatten().collect();
 
@@ -1000,6 +2816,25 @@ mod tests {
         assert_eq!(qqq_vec[googl_idx], "8%");
     }
 
+    #[test]
+    fn test_get_etf_comparison_min_present() {
+        let df = df! {
+            "ETF" => &["SPY", "SPY", "SPY", "QQQ", "QQQ", "VTI", "VTI"],
+            "Symbol" => &["AAPL", "MSFT", "XOM", "AAPL", "NVDA", "AAPL", "MSFT"],
+            "Weight" => &["7%", "6%", "1%", "9%", "8%", "6%", "5%"]
+        }.unwrap();
+
+        let etfs = vec!["SPY".to_string(), "QQQ".to_string(), "VTI".to_string()];
+        let comparison = get_etf_comparison_with_min_present(&df, &etfs, 2).unwrap();
+
+        let symbols: Vec<&str> = comparison.column("Symbol").unwrap().str().unwrap().into_iter().flatten().collect();
+        assert_eq!(symbols, vec!["AAPL", "MSFT"]);
+        assert_eq!(comparison.width(), 4);
+
+        // The default keeps singleton holdings too
+        assert_eq!(get_etf_comparison(&df, &etfs).unwrap().height(), 4);
+    }
+
     #[test]
     fn test_get_etf_comparison_case_insensitive() {
         let df = df! {
@@ -1030,4 +2865,428 @@ mod tests {
         let result = get_etf_comparison(&df, &etfs);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_parse_weight() {
+        assert_eq!(parse_weight("5%"), Some(0.05));
+        assert_eq!(parse_weight(" 12.5 % "), Some(0.125));
+        assert_eq!(parse_weight("0.25"), Some(0.25));
+        assert_eq!(parse_weight("N/A"), None);
+    }
+
+    #[test]
+    fn test_parse_weight_spaced_percent() {
+        for cell in ["5 %", " 5.0% ", "5.00 %", "5.00 % ", "5.0\u{a0}%"] {
+            assert_eq!(parse_weight(cell), Some(0.05), "{:?}", cell);
+        }
+
+        let df = df! {
+            "Weight" => &["5 %", " 5.0% ", "5.00 %"]
+        }.unwrap();
+        assert_eq!(weight_values(&df).unwrap(), vec![Some(0.05); 3]);
+    }
+
+    #[test]
+    fn test_weight_profile() {
+        let df = df! {
+            "ETF" => &["SPY", "SPY", "SPY", "QQQ"],
+            "Symbol" => &["MSFT", "AAPL", "GOOGL", "AAPL"],
+            "Name" => &["Microsoft", "Apple", "Google", "Apple"],
+            "Weight" => &["30%", "50%", "20%", "90%"]
+        }.unwrap();
+
+        let profile = weight_profile(&df, "spy").unwrap();
+
+        let columns = profile.get_column_names();
+        assert_eq!(columns, vec!["Rank", "Symbol", "Weight", "Cumulative_Weight"]);
+        assert_eq!(profile.height(), 3);
+
+        // Sorted by descending weight
+        let symbols: Vec<&str> = profile.column("Symbol").unwrap().str().unwrap()
+            .into_iter().flatten().collect();
+        assert_eq!(symbols, vec!["AAPL", "MSFT", "GOOGL"]);
+
+        let ranks: Vec<u32> = profile.column("Rank").unwrap().u32().unwrap()
+            .into_iter().flatten().collect();
+        assert_eq!(ranks, vec![1, 2, 3]);
+
+        // Cumulative weight reaches the full coverage at the last row
+        let cumulative: Vec<f64> = profile.column("Cumulative_Weight").unwrap().f64().unwrap()
+            .into_iter().flatten().collect();
+        assert!((cumulative[0] - 0.5).abs() < 1e-9);
+        assert!((cumulative[1] - 0.8).abs() < 1e-9);
+        assert!((cumulative[2] - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_weight_profile_unknown_etf() {
+        let df = df! {
+            "ETF" => &["SPY"],
+            "Symbol" => &["AAPL"],
+            "Name" => &["Apple"],
+            "Weight" => &["5%"]
+        }.unwrap();
+
+        assert!(weight_profile(&df, "VTI").is_err());
+    }
+
+    #[test]
+    fn test_average_overlap_identical_funds() {
+        let df = df! {
+            "ETF" => &["SPY", "SPY", "VOO", "VOO", "IVV", "IVV"],
+            "Symbol" => &["AAPL", "MSFT", "AAPL", "MSFT", "AAPL", "MSFT"],
+            "Name" => &["Apple", "Microsoft", "Apple", "Microsoft", "Apple", "Microsoft"],
+            "Weight" => &["60%", "40%", "60%", "40%", "60%", "40%"]
+        }.unwrap();
+
+        let avg = average_overlap(&df).unwrap();
+        assert!((avg - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_average_overlap_disjoint_funds() {
+        let df = df! {
+            "ETF" => &["SPY", "QQQ", "IWF"],
+            "Symbol" => &["AAPL", "MSFT", "GOOGL"],
+            "Name" => &["Apple", "Microsoft", "Google"],
+            "Weight" => &["100%", "100%", "100%"]
+        }.unwrap();
+
+        let avg = average_overlap(&df).unwrap();
+        assert!(avg.abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_weighted_overlap_matrix() {
+        let df = df! {
+            "ETF" => &["SPY", "SPY", "QQQ", "QQQ"],
+            "Symbol" => &["AAPL", "MSFT", "AAPL", "NVDA"],
+            "Name" => &["Apple", "Microsoft", "Apple", "Nvidia"],
+            "Weight" => &["70%", "30%", "20%", "80%"]
+        }.unwrap();
+
+        let (etfs, matrix) = weighted_overlap_matrix(&df).unwrap();
+        assert_eq!(etfs, vec!["QQQ", "SPY"]);
+
+        // Only AAPL is shared; the smaller weight is 20%
+        assert!((matrix[0][1] - 0.2).abs() < 1e-9);
+        assert!((matrix[1][0] - 0.2).abs() < 1e-9);
+        assert!((matrix[1][1] - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_jaccard_matrix() {
+        let df = df! {
+            "ETF" => &["SPY", "SPY", "VOO", "VOO", "GLD"],
+            "Symbol" => &["AAPL", "MSFT", "AAPL", "MSFT", "GOLD"],
+            "Name" => &["Apple", "Microsoft", "Apple", "Microsoft", "Gold"]
+        }.unwrap();
+
+        let matrix = jaccard_matrix(&df).unwrap();
+        assert_eq!(matrix.get_column_names(), vec!["ETF", "GLD", "SPY", "VOO"]);
+
+        // Rows are ordered GLD, SPY, VOO
+        let spy = matrix.column("SPY").unwrap().f64().unwrap();
+        assert!((spy.get(2).unwrap() - 1.0).abs() < 1e-9);
+        assert!(spy.get(0).unwrap().abs() < 1e-9);
+
+        let gld = matrix.column("GLD").unwrap().f64().unwrap();
+        assert!((gld.get(0).unwrap() - 1.0).abs() < 1e-9);
+        assert!(gld.get(2).unwrap().abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_search_holdings_any_and_all() {
+        let df = df! {
+            "ETF" => &["SOXX", "SOXX", "SOXX", "SPY"],
+            "Symbol" => &["NVDA", "AMD", "TSM", "AAPL"],
+            "Name" => &["Nvidia Semiconductor", "Advanced Micro Devices", "Taiwan Semiconductor Chip Co", "Apple"]
+        }.unwrap();
+
+        let queries = vec!["semiconductor".to_string(), "chip".to_string()];
+
+        // OR: union of both terms
+        let any = search_holdings(&df, &queries, QueryMatch::Any).unwrap();
+        let symbols: Vec<&str> = any.column("Symbol").unwrap().str().unwrap().into_iter().flatten().collect();
+        assert_eq!(symbols, vec!["NVDA", "TSM"]);
+
+        // AND: only rows matching both terms
+        let all = search_holdings(&df, &queries, QueryMatch::All).unwrap();
+        let symbols: Vec<&str> = all.column("Symbol").unwrap().str().unwrap().into_iter().flatten().collect();
+        assert_eq!(symbols, vec!["TSM"]);
+
+        // Symbols match too
+        let by_symbol = search_holdings(&df, &["amd".to_string(), "aapl".to_string()], QueryMatch::Any).unwrap();
+        assert_eq!(by_symbol.height(), 2);
+
+        assert!(search_holdings(&df, &[" ".to_string()], QueryMatch::Any).is_err());
+    }
+
+    #[test]
+    fn test_most_common_assets() {
+        let df = df! {
+            "ETF" => &["SPY", "SPY", "SPY", "QQQ", "QQQ", "VOO", "VOO"],
+            "Symbol" => &["AAPL", "MSFT", "JPM", "MSFT", "NVDA", "MSFT", "AAPL"],
+            "Name" => &["Apple", "Microsoft", "JPMorgan", "Microsoft", "Nvidia", "Microsoft", "Apple"],
+            "Weight" => &["5%", "6%", "1%", "8%", "9%", "7%", "4%"]
+        }.unwrap();
+
+        let common = most_common_assets(&df, 3).unwrap();
+        assert_eq!(common.get_column_names(), vec!["Symbol", "Name", "ETF_Count", "ETFs"]);
+
+        // MSFT is in all three ETFs; NVDA and JPM tie on count, NVDA has more weight
+        let symbols: Vec<&str> = common.column("Symbol").unwrap().str().unwrap()
+            .into_iter().flatten().collect();
+        assert_eq!(symbols, vec!["MSFT", "AAPL", "NVDA"]);
+    }
+
+    #[test]
+    fn test_data_quality_warnings() {
+        let df = df! {
+            "ETF" => &["FULL", "FULL", "PART", "PART", "PART"],
+            "Symbol" => &["AAPL", "MSFT", "AAPL", "PART-2", "NVDA"],
+            "Name" => &["Apple", "Microsoft", "Apple", "Cash", "Nvidia"],
+            "Weight" => &[Some("60%"), Some("40%"), Some("50%"), Some("10%"), None]
+        }.unwrap();
+
+        let warnings = data_quality_warnings(&df).unwrap();
+        let kinds: Vec<(WarningKind, Option<&str>)> = warnings
+            .iter()
+            .map(|w| (w.kind, w.etf.as_deref()))
+            .collect();
+        assert_eq!(kinds, vec![
+            (WarningKind::SynthesizedSymbols, Some("PART")),
+            (WarningKind::NullWeights, Some("PART")),
+            (WarningKind::Coverage, Some("PART")),
+        ]);
+        assert!(warnings[2].message.contains("60.00%"));
+    }
+
+    #[test]
+    fn test_exclude_synthetic_symbols() {
+        let df = df! {
+            "ETF" => &["SPY", "SPY", "SPY", "QQQ", "QQQ"],
+            "Symbol" => &["AAPL", "SPY-3", "SPY-X", "QQQ-12", "BRK-B"],
+            "Name" => &["Apple", "Cash", "Other", "Futures", "Berkshire"]
+        }.unwrap();
+
+        let (filtered, removed) = exclude_synthetic_symbols(&df).unwrap();
+        assert_eq!(removed, 2);
+
+        let symbols: Vec<&str> = filtered.column("Symbol").unwrap().str().unwrap()
+            .into_iter().flatten().collect();
+        assert_eq!(symbols, vec!["AAPL", "SPY-X", "BRK-B"]);
+    }
+
+    #[test]
+    fn test_filter_min_holdings() {
+        let df = df! {
+            "ETF" => &["TINY", "BIG", "BIG", "BIG", "BIG", "BIG"],
+            "Symbol" => &["AAPL", "AAPL", "MSFT", "NVDA", "JPM", "XOM"],
+            "Name" => &["Apple", "Apple", "Microsoft", "Nvidia", "JPMorgan", "Exxon"]
+        }.unwrap();
+
+        let (filtered, dropped) = filter_min_holdings(&df, 5).unwrap();
+        assert_eq!(dropped, vec!["TINY"]);
+        assert_eq!(filtered.height(), 5);
+        assert_eq!(get_etf_list(&filtered).unwrap(), vec!["BIG"]);
+
+        let (unfiltered, dropped) = filter_min_holdings(&df, 1).unwrap();
+        assert!(dropped.is_empty());
+        assert_eq!(unfiltered.height(), 6);
+    }
+
+    #[test]
+    fn test_overlap_with_external_index() {
+        let df = df! {
+            "ETF" => &["SPY", "SPY", "ARKK", "ARKK"],
+            "Symbol" => &["AAPL", "MSFT", "TSLA", "AAPL"],
+            "Name" => &["Apple", "Microsoft", "Tesla", "Apple"],
+            "Weight" => &["60%", "40%", "90%", "10%"]
+        }.unwrap();
+        let reference = df! {
+            "ETF" => &["SP500", "SP500", "SP500"],
+            "Symbol" => &["AAPL", "MSFT", "NVDA"],
+            "Name" => &["Apple", "Microsoft", "Nvidia"],
+            "Weight" => &["50%", "30%", "20%"]
+        }.unwrap();
+
+        let overlap = overlap_with_external_index(&df, &reference).unwrap();
+        assert_eq!(overlap.get_column_names(), vec!["ETF", "Overlap"]);
+
+        // SPY: min(60,50) + min(40,30) = 80%; ARKK: min(10,50) = 10%
+        let etfs: Vec<&str> = overlap.column("ETF").unwrap().str().unwrap().into_iter().flatten().collect();
+        assert_eq!(etfs, vec!["SPY", "ARKK"]);
+
+        let values = overlap.column("Overlap").unwrap().f64().unwrap();
+        assert!((values.get(0).unwrap() - 0.8).abs() < 1e-9);
+        assert!((values.get(1).unwrap() - 0.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_weight_diff() {
+        let df = df! {
+            "ETF" => &["SPY", "SPY", "VOO", "VOO"],
+            "Symbol" => &["AAPL", "MSFT", "AAPL", "NVDA"],
+            "Name" => &["Apple", "Microsoft", "Apple", "Nvidia"],
+            "Weight" => &["5%", "1%", "3%", "4%"]
+        }.unwrap();
+
+        let diff = weight_diff(&df, "SPY", "voo").unwrap();
+        assert_eq!(diff.get_column_names(), vec!["Symbol", "Weight_A", "Weight_B", "Diff"]);
+
+        // Sorted by |Diff|: NVDA (-4%), AAPL (+2%), MSFT (+1%)
+        let symbols: Vec<&str> = diff.column("Symbol").unwrap().str().unwrap()
+            .into_iter().flatten().collect();
+        assert_eq!(symbols, vec!["NVDA", "AAPL", "MSFT"]);
+
+        let diffs = diff.column("Diff").unwrap().f64().unwrap();
+        assert!((diffs.get(0).unwrap() + 0.04).abs() < 1e-9);
+        assert!((diffs.get(1).unwrap() - 0.02).abs() < 1e-9);
+
+        // Absences count as 0
+        let weight_a = diff.column("Weight_A").unwrap().f64().unwrap();
+        assert_eq!(weight_a.get(0), Some(0.0));
+
+        assert!(weight_diff(&df, "SPY", "QQQ").is_err());
+    }
+
+    #[test]
+    fn test_pair_breakdown() {
+        let df = df! {
+            "ETF" => &["SPY", "SPY", "SPY", "QQQ", "QQQ"],
+            "Symbol" => &["AAPL", "MSFT", "JPM", "AAPL", "NVDA"],
+            "Name" => &["Apple", "Microsoft", "JPMorgan", "Apple", "Nvidia"]
+        }.unwrap();
+
+        // SPY only: MSFT, JPM; QQQ only: NVDA; both: AAPL
+        assert_eq!(pair_breakdown(&df, "spy", "QQQ").unwrap(), (2, 1, 1));
+        assert_eq!(pair_breakdown(&df, "QQQ", "SPY").unwrap(), (1, 2, 1));
+
+        assert!(pair_breakdown(&df, "SPY", "VOO").is_err());
+    }
+
+    #[test]
+    fn test_top_overlap_pairs() {
+        let df = df! {
+            "ETF" => &["SPY", "SPY", "VOO", "VOO", "QQQ", "QQQ"],
+            "Symbol" => &["AAPL", "MSFT", "AAPL", "MSFT", "AAPL", "NVDA"],
+            "Name" => &["Apple", "Microsoft", "Apple", "Microsoft", "Apple", "Nvidia"],
+            "Weight" => &["50%", "50%", "55%", "45%", "10%", "90%"]
+        }.unwrap();
+
+        let pairs = top_overlap_pairs(&df, 2).unwrap();
+        assert_eq!(pairs.get_column_names(), vec!["ETF_A", "ETF_B", "Overlap"]);
+        assert_eq!(pairs.height(), 2);
+
+        // SPY and VOO are the most similar pair
+        let etf_a = pairs.column("ETF_A").unwrap().str().unwrap();
+        let etf_b = pairs.column("ETF_B").unwrap().str().unwrap();
+        assert_eq!(etf_a.get(0), Some("SPY"));
+        assert_eq!(etf_b.get(0), Some("VOO"));
+
+        let overlap = pairs.column("Overlap").unwrap().f64().unwrap();
+        assert!((overlap.get(0).unwrap() - 0.95).abs() < 1e-9);
+        assert!(overlap.get(0).unwrap() >= overlap.get(1).unwrap());
+    }
+
+    #[test]
+    fn test_unique_exposure() {
+        let df = df! {
+            "ETF" => &["SPY", "SPY", "SPY", "QQQ", "QQQ"],
+            "Symbol" => &["AAPL", "JPM", "XOM", "AAPL", "NVDA"],
+            "Name" => &["Apple", "JPMorgan", "Exxon", "Apple", "Nvidia"],
+            "Weight" => &["40%", "35%", "25%", "30%", "70%"]
+        }.unwrap();
+
+        let exposure = unique_exposure(&df).unwrap();
+        assert_eq!(exposure.get_column_names(), vec!["ETF", "Unique_Weight", "Shared_Weight"]);
+
+        // Sorted by ETF: QQQ, SPY
+        let unique = exposure.column("Unique_Weight").unwrap().f64().unwrap();
+        let shared = exposure.column("Shared_Weight").unwrap().f64().unwrap();
+
+        // QQQ: NVDA unique (70%), AAPL shared (30%)
+        assert!((unique.get(0).unwrap() - 0.70).abs() < 1e-9);
+        assert!((shared.get(0).unwrap() - 0.30).abs() < 1e-9);
+
+        // SPY: JPM + XOM unique (60%), AAPL shared (40%)
+        assert!((unique.get(1).unwrap() - 0.60).abs() < 1e-9);
+        assert!((shared.get(1).unwrap() - 0.40).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_weight_outliers() {
+        // 20 holdings at 1% and one data-entry error at 50%
+        let mut etfs = vec!["SPY"; 21];
+        let mut symbols: Vec<String> = (0..20).map(|i| format!("S{}", i)).collect();
+        let mut weights = vec!["1%".to_string(); 20];
+        symbols.push("BAD".to_string());
+        weights.push("50%".to_string());
+
+        // A second ETF with no outliers
+        etfs.extend(["QQQ", "QQQ"]);
+        symbols.extend(["AAPL".to_string(), "MSFT".to_string()]);
+        weights.extend(["40%".to_string(), "60%".to_string()]);
+
+        let names = symbols.clone();
+        let df = df! {
+            "ETF" => etfs,
+            "Symbol" => symbols,
+            "Name" => names,
+            "Weight" => weights
+        }.unwrap();
+
+        let outliers = weight_outliers(&df, 3.0).unwrap();
+        assert_eq!(outliers.get_column_names(), vec!["ETF", "Symbol", "Weight", "ZScore"]);
+        assert_eq!(outliers.height(), 1);
+
+        let symbol = outliers.column("Symbol").unwrap().str().unwrap();
+        assert_eq!(symbol.get(0), Some("BAD"));
+        let z = outliers.column("ZScore").unwrap().f64().unwrap();
+        assert!(z.get(0).unwrap() > 3.0);
+    }
+
+    #[test]
+    fn test_gini_coefficient() {
+        let df = df! {
+            "ETF" => &["EQL", "EQL", "EQL", "EQL", "TOP", "TOP", "TOP", "TOP"],
+            "Symbol" => &["A", "B", "C", "D", "A", "B", "C", "D"],
+            "Name" => &["A", "B", "C", "D", "A", "B", "C", "D"],
+            "Weight" => &["25%", "25%", "25%", "25%", "97%", "1%", "1%", "1%"]
+        }.unwrap();
+
+        let gini = gini_coefficient(&df).unwrap();
+        assert_eq!(gini.get_column_names(), vec!["ETF", "Gini"]);
+
+        let values = gini.column("Gini").unwrap().f64().unwrap();
+        // Uniform distribution
+        assert!(values.get(0).unwrap().abs() < 1e-9);
+        // One dominant holding (maximum for 4 holdings is 0.75)
+        assert!(values.get(1).unwrap() > 0.7);
+    }
+
+    #[test]
+    fn test_overlap_vs_benchmark() {
+        let df = df! {
+            "ETF" => &["VTI", "VTI", "SPY", "SPY", "QQQ"],
+            "Symbol" => &["AAPL", "MSFT", "AAPL", "JPM", "NVDA"],
+            "Name" => &["Apple", "Microsoft", "Apple", "JPMorgan", "Nvidia"],
+            "Weight" => &["60%", "40%", "30%", "70%", "100%"]
+        }.unwrap();
+
+        let result = overlap_vs_benchmark(&df, "vti").unwrap();
+        assert_eq!(result.get_column_names(), vec!["ETF", "Overlap"]);
+
+        let etfs: Vec<&str> = result.column("ETF").unwrap().str().unwrap()
+            .into_iter().flatten().collect();
+        assert_eq!(etfs, vec!["VTI", "SPY", "QQQ"]);
+
+        let overlap = result.column("Overlap").unwrap().f64().unwrap();
+        assert!((overlap.get(0).unwrap() - 1.0).abs() < 1e-9);
+        assert!((overlap.get(1).unwrap() - 0.3).abs() < 1e-9);
+        assert!(overlap.get(2).unwrap().abs() < 1e-9);
+
+        assert!(overlap_vs_benchmark(&df, "IWF").is_err());
+    }
 }
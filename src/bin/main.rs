@@ -1,8 +1,39 @@
-use etf_analyzer::{analysis, cli, config, io, portfolio, report, Result};
+use etf_analyzer::{analysis, cli, config, fixtures, io, locale, portfolio, report, AnalyzerContext, Result};
+use polars::prelude::DataFrame;
+
+/// Numeric holdings columns that are never prices (skipped by drawdowns without --price-cols)
+const HOLDINGS_NUMERIC_COLUMNS: [&str; 3] = ["No.", "Shares", "Rank_In_ETF"];
 
 fn main() -> Result<()> {
     let mut args = cli::parse_args();
 
+    // Print build information for bug reports
+    if args.build_info {
+        println!("{}", cli::build_info_json());
+        return Ok(());
+    }
+
+    // Write sample holdings files for trying the analyzer out
+    if let Some(dir) = &args.generate_sample {
+        let options = io::ExportOptions {
+            force: args.force,
+            default_yes: args.default_yes,
+            ..Default::default()
+        };
+        for (etf, rows) in fixtures::SAMPLE_ETFS {
+            if let Some(path) = fixtures::write_sample_holdings(dir, etf, *rows, &options)? {
+                println!("Wrote {} holdings for {} to {}", rows, etf, path.display());
+            }
+        }
+        println!("Try: etf_analyzer -d {} -f summary", dir);
+        return Ok(());
+    }
+
+    // Validate the configuration file without loading any data
+    if args.function == "validate-config" {
+        return validate_config();
+    }
+
     // Load configuration from default locations and merge with CLI args
     if let Ok(Some(cfg)) = config::Config::load_default() {
         if args.verbose {
@@ -11,14 +42,37 @@ fn main() -> Result<()> {
         cfg.merge_with_cli(&mut args);
     }
 
+    // A config file naming validate-config as its default function still runs it before any data loads
+    if args.function == "validate-config" {
+        return validate_config();
+    }
+
+    // Reject unknown functions before loading any data
+    if !cli::FUNCTIONS.contains(&args.function.as_str()) {
+        return Err(unknown_function_error(&args.function));
+    }
+
     if args.verbose {
         println!("ETF Analyzer starting...");
     }
 
-    // Validate that either -d or -i is provided
-    if args.data_dir.is_none() && args.import.is_none() {
+    // The summary JSON sidecar is named after the output file
+    if args.with_summary_json && args.output.is_none() && args.output_template.is_none() {
+        return Err(etf_analyzer::Error::Other(
+            "--with-summary-json requires --output (-o) to be specified".to_string()
+        ));
+    }
+
+    // Collect explicitly listed holdings files from --files and --files-from
+    let mut input_files: Vec<String> = args.files.clone().unwrap_or_default();
+    if let Some(list_path) = &args.files_from {
+        input_files.extend(io::read_file_list(list_path)?);
+    }
+
+    // Validate that either -d or -i (or an explicit file list) is provided
+    if args.data_dir.is_none() && args.import.is_none() && input_files.is_empty() {
         return Err(etf_analyzer::Error::Other(
-            "Either --data-dir (-d) or --import (-i) must be specified".to_string()
+            "Either --data-dir (-d) or --import (-i) must be specified (or --files / --files-from)".to_string()
         ));
     }
 
@@ -29,43 +83,97 @@ fn main() -> Result<()> {
         args.weight_col.clone(),
         args.shares_col.clone(),
         args.number_col.clone(),
+        args.weight_whole_col.clone(),
+        args.weight_frac_col.clone(),
     );
 
     if args.verbose && (args.symbol_col.is_some() || args.name_col.is_some() ||
                         args.weight_col.is_some() || args.shares_col.is_some() ||
-                        args.number_col.is_some()) {
+                        args.number_col.is_some() || args.weight_whole_col.is_some() ||
+                        args.weight_frac_col.is_some()) {
         println!("Using custom column configuration:");
         println!("  Symbol column: {}", column_config.symbol_col);
         println!("  Name column: {}", column_config.name_col);
         println!("  Weight column: {}", column_config.weight_col);
         println!("  Shares column: {}", column_config.shares_col);
         println!("  Number column: {}", column_config.number_col);
+        if let (Some(whole), Some(frac)) = (&column_config.weight_whole_col, &column_config.weight_frac_col) {
+            println!("  Split weight columns: {} + {}", whole, frac);
+        }
     }
 
+    // Separators for human-readable numbers; data files keep plain numbers
+    let number_format = match &args.locale {
+        Some(locale) => {
+            let format = locale::NumberFormat::from_locale(locale)?;
+            format.apply_to_tables();
+            format
+        }
+        None => locale::NumberFormat::default(),
+    };
+
+    // Order of entries in comma-separated ETF/asset lists
+    let list_order = analysis::ListOrder::from_str(&args.list_order);
+
+    // Options shared by every export below
+    let parquet_compression = io::ParquetCodec::try_from_str(&args.parquet_compression).ok_or_else(|| {
+        etf_analyzer::Error::Other(format!(
+            "Unknown --parquet-compression '{}' (expected snappy, zstd, gzip or none)",
+            args.parquet_compression
+        ))
+    })?;
+    let export_options = io::ExportOptions {
+        force: args.force,
+        force_formats: io::FileFormat::parse_names(args.force_format.as_deref().unwrap_or_default())?,
+        keep_formats: io::FileFormat::parse_names(args.keep_format.as_deref().unwrap_or_default())?,
+        default_yes: args.default_yes,
+        null_value: args.null_value.clone(),
+        parquet_compression,
+        rename: io::parse_column_renames(args.rename_out.as_deref().unwrap_or_default())?,
+        list_as_array: args.list_as_array,
+    };
+
     // Load DataFrame from either import file or data directory
     let mut df = if let Some(import_path) = &args.import {
         if args.verbose {
             println!("Importing DataFrame from: {}", import_path);
         }
         io::import_dataframe(import_path)?
+    } else if !input_files.is_empty() {
+        if args.verbose {
+            println!("Loading {} listed holdings files", input_files.len());
+        }
+        io::load_holdings_files_with_config(input_files, &column_config)?
     } else if let Some(data_dir) = &args.data_dir {
         if args.verbose {
             println!("Loading portfolio from directory: {}", data_dir);
         }
         io::load_portfolio_from_directory_with_config(data_dir, &column_config)?
     } else {
-        unreachable!("Either data_dir or import must be Some");
+        unreachable!("Either data_dir, import or input files must be provided");
     };
 
+    // Trim padded Symbol/Name/ETF values so they group together
+    if !args.no_trim {
+        df = io::trim_string_columns(&df)?;
+    }
+
+    // Show what the weight parser is dealing with before analysis
+    if args.verbose && df.column("Weight").is_ok() {
+        let report = io::inspect_weight_column(&df, "Weight")?;
+        println!("Weight column: {}", report.summary());
+    }
+
     // Apply ETF filter if specified
-    if let Some(etf_list) = &args.etfs {
+    if let Some(etf_list) = args.etfs.clone() {
         if args.verbose {
             println!("Filtering to ETFs: {}", etf_list.join(", "));
         }
-        df = analysis::filter_etfs(&df, etf_list)?;
+        let before = df.clone();
+        df = analysis::filter_etfs(&df, &etf_list)?;
 
         if args.verbose {
-            println!("Filtered DataFrame contains {} rows", df.height());
+            print_filter_report(&before, &df, &number_format)?;
         }
 
         if df.height() == 0 {
@@ -73,23 +181,106 @@ fn main() -> Result<()> {
                 "No data found for the specified ETFs. Check that ETF symbols are correct.".to_string()
             ));
         }
+
+        // Later functions (compare, pair, profile, ...) need real ETF names, not patterns
+        let expanded = analysis::expand_etf_patterns(&df, &etf_list)?;
+        if args.verbose && expanded != etf_list {
+            println!("Expanded ETF patterns to: {}", expanded.join(", "));
+        }
+        args.etfs = Some(expanded);
+    }
+
+    // Derive the output filename from --output-template when --output is absent
+    // Done after pattern expansion so {etfs} lists the resolved ETFs, not the raw patterns
+    if args.output.is_none() {
+        if let Some(template) = &args.output_template {
+            let output = cli::expand_output_template(template, &args.function, args.etfs.as_deref(), &cli::today())?;
+            if args.verbose {
+                println!("Output file from template: {}", output);
+            }
+            args.output = Some(output);
+        }
+    }
+
+    // Gzip CSV output by giving it a .gz extension; exports pick the format from the path
+    if args.gzip {
+        if let Some(output) = &args.output {
+            match std::path::Path::new(output).extension().and_then(|ext| ext.to_str()) {
+                None => args.output = Some(format!("{}.csv.gz", output)),
+                Some(ext) if ext.eq_ignore_ascii_case("csv") => args.output = Some(format!("{}.gz", output)),
+                Some(_) if io::is_csv_gz(output) => {}
+                Some(_) => {
+                    return Err(etf_analyzer::Error::Other(format!(
+                        "--gzip only applies to CSV output, but --output is '{}' (use a .csv path or drop --gzip)",
+                        output
+                    )));
+                }
+            }
+        }
+    }
+
+    // Drop synthesized {ETF}-{No.} symbols for a real-tickers-only analysis
+    if args.exclude_synthetic {
+        let (filtered, removed) = analysis::exclude_synthetic_symbols(&df)?;
+
+        if args.verbose {
+            println!("Excluded {} synthesized symbol(s)", removed);
+            print_filter_report(&df, &filtered, &number_format)?;
+        }
+
+        df = filtered;
+    }
+
+    // Drop ETFs too small to analyze if requested
+    if let Some(min_holdings) = args.min_holdings {
+        let (filtered, dropped) = analysis::filter_min_holdings(&df, min_holdings)?;
+
+        if args.verbose && !dropped.is_empty() {
+            println!(
+                "Dropped {} ETF(s) with fewer than {} holdings: {}",
+                dropped.len(),
+                min_holdings,
+                dropped.join(", ")
+            );
+        }
+
+        if filtered.height() == 0 {
+            return Err(etf_analyzer::Error::Other(
+                format!("No ETFs have at least {} holdings", min_holdings)
+            ));
+        }
+
+        if args.verbose {
+            print_filter_report(&df, &filtered, &number_format)?;
+        }
+
+        df = filtered;
     }
 
     // Handle the export function
     if args.function == "export" {
         if let Some(output_path) = &args.output {
             // Add .parquet extension if no extension is present
-            let output_path_with_ext = if std::path::Path::new(output_path).extension().is_none() {
-                format!("{}.parquet", output_path)
-            } else {
-                output_path.to_string()
-            };
+            let output_path_with_ext = with_default_extension(output_path, "parquet");
+
+            if args.add_pct_column {
+                if args.verbose {
+                    println!("Adding Weight_Pct column");
+                }
+                df = io::add_weight_pct_column(&df)?;
+            }
+
+            if args.add_rank_column {
+                if args.verbose {
+                    println!("Adding Rank_In_ETF column");
+                }
+                df = analysis::rank_within_etf(&df)?;
+            }
 
             if args.verbose {
                 println!("Exporting DataFrame to: {}", output_path_with_ext);
             }
-            let written = io::export_dataframe(&df, &output_path_with_ext, args.force)?;
-            if written {
+            if write_output(&args, &df, &output_path_with_ext, &export_options)? {
                 println!("Successfully exported to: {}", output_path_with_ext);
             }
         } else {
@@ -100,33 +291,29 @@ fn main() -> Result<()> {
         return Ok(());
     }
 
+    // Parse weights once for the weight-based functions below
+    let ctx = AnalyzerContext::new(df.clone(), column_config.clone())?;
+
     // Handle the summary function
     if args.function == "summary" {
         if args.verbose {
             println!("Generating ETF summary...");
         }
 
-        let summary_df = analysis::get_etf_summary(&df)?;
+        let summary_df = analysis::get_etf_summary_with_list_order(&df, list_order)?;
 
         // Always print summary statistics to stdout
-        let summary = analysis::summarize_etfs(&summary_df)?;
+        let summary = analysis::summarize_etfs_with_format(&summary_df, &number_format)?;
         println!("{}", summary);
 
         // If output file is specified, save with default .csv extension if no extension provided
-        if let Some(output_path) = &args.output {
-            // Add .csv extension if no extension is present
-            let output_path_with_ext = if std::path::Path::new(output_path).extension().is_none() {
-                format!("{}.csv", output_path)
-            } else {
-                output_path.to_string()
-            };
-
-            if args.verbose {
-                println!("Saving ETF summary to: {}", output_path_with_ext);
-            }
-            let written = io::export_dataframe(&summary_df, &output_path_with_ext, args.force)?;
-            if written {
-                println!("ETF summary saved to: {}", output_path_with_ext);
+        if let Some(output_path) = save_output(&args, &summary_df, "csv", "ETF summary", &export_options)? {
+            if args.with_summary_json {
+                let warnings = analysis::data_quality_warnings(&df)?;
+                let json = report::etf_summary_json(&summary_df, &warnings)?;
+                if let Some(sidecar_path) = report::write_summary_json(&output_path, &json, &export_options)? {
+                    println!("Summary JSON saved to: {}", sidecar_path);
+                }
             }
         }
 
@@ -141,27 +328,49 @@ fn main() -> Result<()> {
             println!("Aggregating assets by symbol...");
         }
 
-        let assets_df = analysis::aggregate_assets(&df, sort_by)?;
+        let weight_agg = analysis::WeightAgg::from_str(&args.asset_weight_agg);
+        let mut assets_df = analysis::aggregate_assets_with_weight_agg(&df, sort_by, weight_agg, list_order)?;
+
+        // Restrict to assets held by a range of ETF counts if requested
+        if args.min_etfs.is_some() || args.max_etfs.is_some() {
+            let min = args.min_etfs.unwrap_or(1);
+            if args.verbose {
+                match args.max_etfs {
+                    Some(max) => println!("Keeping assets held by {} to {} ETFs", min, max),
+                    None => println!("Keeping assets held by at least {} ETFs", min),
+                }
+            }
+            assets_df = analysis::filter_by_etf_count(&assets_df, min, args.max_etfs)?;
+        }
 
         // Always print summary to stdout
-        let summary = analysis::summarize_assets(&assets_df)?;
+        let summary = analysis::summarize_assets_with_format(&assets_df, &number_format)?;
         println!("{}", summary);
 
-        // If output file is specified, save with default .csv extension if no extension provided
-        if let Some(output_path) = &args.output {
-            // Add .csv extension if no extension is present
-            let output_path_with_ext = if std::path::Path::new(output_path).extension().is_none() {
-                format!("{}.csv", output_path)
-            } else {
-                output_path.to_string()
-            };
-
+        // Optionally write the distribution behind the summary as CSV
+        if let Some(dist_path) = &args.dist_output {
             if args.verbose {
-                println!("Saving assets to: {}", output_path_with_ext);
+                println!("Saving asset distribution to: {}", dist_path);
             }
-            let written = io::export_dataframe(&assets_df, &output_path_with_ext, args.force)?;
-            if written {
-                println!("Assets saved to: {}", output_path_with_ext);
+            check_output_path(&args, dist_path)?;
+            // --rename-out targets the main output's columns, not ETF_Count/Asset_Count
+            let dist_options = io::ExportOptions {
+                rename: Vec::new(),
+                ..export_options.clone()
+            };
+            if report::asset_distribution_csv(&assets_df, dist_path, &dist_options)? {
+                println!("Asset distribution saved to: {}", dist_path);
+            }
+        }
+
+        // If output file is specified, save with default .csv extension if no extension provided
+        if let Some(output_path) = save_output(&args, &assets_df, "csv", "assets", &export_options)? {
+            if args.with_summary_json {
+                let warnings = analysis::data_quality_warnings(&df)?;
+                let json = report::assets_summary_json(&assets_df, &warnings)?;
+                if let Some(sidecar_path) = report::write_summary_json(&output_path, &json, &export_options)? {
+                    println!("Summary JSON saved to: {}", sidecar_path);
+                }
             }
         }
 
@@ -176,25 +385,10 @@ fn main() -> Result<()> {
 
         let unique_df = analysis::get_unique_assets(&df)?;
 
-        println!("Found {} unique assets (appear in only one ETF)", unique_df.height());
+        println!("Found {} unique assets (appear in only one ETF)", number_format.count(unique_df.height() as u64));
 
         // If output file is specified, save with default .csv extension if no extension provided
-        if let Some(output_path) = &args.output {
-            // Add .csv extension if no extension is present
-            let output_path_with_ext = if std::path::Path::new(output_path).extension().is_none() {
-                format!("{}.csv", output_path)
-            } else {
-                output_path.to_string()
-            };
-
-            if args.verbose {
-                println!("Saving unique assets to: {}", output_path_with_ext);
-            }
-            let written = io::export_dataframe(&unique_df, &output_path_with_ext, args.force)?;
-            if written {
-                println!("Unique assets saved to: {}", output_path_with_ext);
-            }
-        }
+        save_output(&args, &unique_df, "csv", "unique assets", &export_options)?;
 
         return Ok(());
     }
@@ -207,27 +401,12 @@ fn main() -> Result<()> {
             println!("Finding overlapping assets (appear in multiple ETFs)...");
         }
 
-        let overlap_df = analysis::get_overlap_assets(&df, sort_by)?;
+        let overlap_df = analysis::get_overlap_assets_with_list_order(&df, sort_by, list_order)?;
 
-        println!("Found {} overlapping assets (appear in multiple ETFs)", overlap_df.height());
+        println!("Found {} overlapping assets (appear in multiple ETFs)", number_format.count(overlap_df.height() as u64));
 
         // If output file is specified, save with default .csv extension if no extension provided
-        if let Some(output_path) = &args.output {
-            // Add .csv extension if no extension is present
-            let output_path_with_ext = if std::path::Path::new(output_path).extension().is_none() {
-                format!("{}.csv", output_path)
-            } else {
-                output_path.to_string()
-            };
-
-            if args.verbose {
-                println!("Saving overlapping assets to: {}", output_path_with_ext);
-            }
-            let written = io::export_dataframe(&overlap_df, &output_path_with_ext, args.force)?;
-            if written {
-                println!("Overlapping assets saved to: {}", output_path_with_ext);
-            }
-        }
+        save_output(&args, &overlap_df, "csv", "overlapping assets", &export_options)?;
 
         return Ok(());
     }
@@ -240,29 +419,14 @@ fn main() -> Result<()> {
             println!("Creating asset-to-ETF mapping...");
         }
 
-        let mapping_df = analysis::get_asset_mapping(&df, sort_by)?;
+        let mapping_df = analysis::get_asset_mapping_with_list_order(&df, sort_by, list_order)?;
 
         // Always print summary to stdout
-        let summary = analysis::summarize_assets(&mapping_df)?;
+        let summary = analysis::summarize_assets_with_format(&mapping_df, &number_format)?;
         println!("{}", summary);
 
         // If output file is specified, save with default .csv extension if no extension provided
-        if let Some(output_path) = &args.output {
-            // Add .csv extension if no extension is present
-            let output_path_with_ext = if std::path::Path::new(output_path).extension().is_none() {
-                format!("{}.csv", output_path)
-            } else {
-                output_path.to_string()
-            };
-
-            if args.verbose {
-                println!("Saving asset mapping to: {}", output_path_with_ext);
-            }
-            let written = io::export_dataframe(&mapping_df, &output_path_with_ext, args.force)?;
-            if written {
-                println!("Asset mapping saved to: {}", output_path_with_ext);
-            }
-        }
+        save_output(&args, &mapping_df, "csv", "asset mapping", &export_options)?;
 
         return Ok(());
     }
@@ -282,33 +446,660 @@ fn main() -> Result<()> {
             ));
         };
 
-        let comparison_df = analysis::get_etf_comparison(&df, &etf_list)?;
+        let comparison_df = match args.min_present {
+            Some(min_present) => analysis::get_etf_comparison_with_min_present(&df, &etf_list, min_present)?,
+            None => analysis::get_etf_comparison(&df, &etf_list)?,
+        };
 
         // Print comparison info to stdout
-        println!("Comparing {} ETFs across {} unique assets", etf_list.len(), comparison_df.height());
+        println!("Comparing {} ETFs across {} unique assets", etf_list.len(), number_format.count(comparison_df.height() as u64));
 
         // Require output file for comparison
-        if let Some(output_path) = &args.output {
-            // Add .csv extension if no extension is present
-            let output_path_with_ext = if std::path::Path::new(output_path).extension().is_none() {
-                format!("{}.csv", output_path)
-            } else {
-                output_path.to_string()
-            };
+        if args.output.is_none() {
+            return Err(etf_analyzer::Error::Other(
+                "Compare function requires --output (-o) to be specified".to_string()
+            ));
+        }
+        save_output(&args, &comparison_df, "csv", "comparison", &export_options)?;
+
+        return Ok(());
+    }
+
+    // Handle the profile function
+    if args.function == "profile" {
+        if args.verbose {
+            println!("Building ETF weight profile...");
+        }
+
+        // Require exactly one ETF for the profile
+        let etf = match &args.etfs {
+            Some(etfs) if etfs.len() == 1 => etfs[0].clone(),
+            _ => {
+                return Err(etf_analyzer::Error::Other(
+                    "Profile function requires exactly one ETF in --etfs".to_string()
+                ));
+            }
+        };
+
+        let profile_df = analysis::weight_profile(&df, &etf)?;
+
+        // Always print summary to stdout
+        let summary = analysis::summarize_weight_profile_with_format(&profile_df, &etf, 10, &number_format)?;
+        println!("{}", summary);
+
+        // If output file is specified, save with default .csv extension if no extension provided
+        save_output(&args, &profile_df, "csv", "weight profile", &export_options)?;
+
+        return Ok(());
+    }
 
+    // Handle the avg-overlap function
+    if args.function == "avg-overlap" {
+        if args.verbose {
+            println!("Calculating average pairwise weighted overlap...");
+        }
+
+        let avg = analysis::average_overlap(&df)?;
+
+        // Print a single number so the result is easy to consume from scripts
+        println!("{}", number_format.number(avg, 4));
+
+        return Ok(());
+    }
+
+    // Handle the overlap-pairs function
+    if args.function == "overlap-pairs" {
+        let top = args.top.unwrap_or(10);
+
+        if args.verbose {
+            println!("Ranking top {} overlapping ETF pairs...", top);
+        }
+
+        let pairs_df = analysis::top_overlap_pairs(&df, top)?;
+
+        // Print the ranked pairs to stdout
+        println!("Top {} overlapping ETF pairs:", pairs_df.height());
+        let etf_a = pairs_df.column("ETF_A")?.str()?;
+        let etf_b = pairs_df.column("ETF_B")?.str()?;
+        let overlap = pairs_df.column("Overlap")?.f64()?;
+        for i in 0..pairs_df.height() {
+            println!(
+                "  {} / {}: {}",
+                etf_a.get(i).unwrap_or(""),
+                etf_b.get(i).unwrap_or(""),
+                number_format.percent(overlap.get(i).unwrap_or(0.0), 2)
+            );
+        }
+
+        // If output file is specified, save with default .csv extension if no extension provided
+        save_output(&args, &pairs_df, "csv", "overlap pairs", &export_options)?;
+
+        return Ok(());
+    }
+
+    // Handle the portfolio-return function
+    if args.function == "portfolio-return" {
+        let returns_file = args.returns_file.as_deref().ok_or_else(|| {
+            etf_analyzer::Error::Other("Portfolio-return function requires --returns-file".to_string())
+        })?;
+        let returns = io::read_returns_file(returns_file)?;
+
+        // Equal-weight portfolio of the loaded ETFs, as in portfolio-state
+        let portfolio = portfolio::Portfolio::new(analysis::get_etf_list(&df)?);
+        if args.verbose {
+            println!("Weighting {} ETFs equally: {}", portfolio.etfs.len(), portfolio.etfs.join(", "));
+        }
+
+        let portfolio_return = portfolio.calculate_portfolio_return_from_map(&returns)?;
+        println!("Portfolio return: {}", number_format.percent(portfolio_return, 2));
+
+        return Ok(());
+    }
+
+    // Handle the portfolio-state function
+    if args.function == "portfolio-state" {
+        let mut portfolio = portfolio::Portfolio::new(analysis::get_etf_list(&df)?);
+        portfolio.load_data(df.clone())?;
+
+        let state = if args.format.eq_ignore_ascii_case("json") {
+            portfolio.to_json()?
+        } else {
+            portfolio.summary()
+        };
+        println!("{}", state);
+
+        // If output file is specified, write the state as-is
+        if let Some(output_path) = &args.output {
             if args.verbose {
-                println!("Saving comparison to: {}", output_path_with_ext);
+                println!("Saving portfolio state to: {}", output_path);
             }
-            let written = io::export_dataframe(&comparison_df, &output_path_with_ext, args.force)?;
-            if written {
-                println!("Comparison saved to: {}", output_path_with_ext);
+            check_output_path(&args, output_path)?;
+            if io::write_text_with_options(output_path, &format!("{}\n", state), &export_options)? {
+                println!("Portfolio state saved to: {}", output_path);
             }
-        } else {
+        }
+
+        return Ok(());
+    }
+
+    // Handle the search function
+    if args.function == "search" {
+        let queries = args.query.clone().unwrap_or_default();
+        if queries.is_empty() {
             return Err(etf_analyzer::Error::Other(
-                "Compare function requires --output (-o) to be specified".to_string()
+                "Search function requires --query to be specified".to_string()
+            ));
+        }
+        let match_mode = analysis::QueryMatch::from_str(&args.match_mode);
+
+        if args.verbose {
+            println!("Searching holdings for: {}", queries.join(", "));
+        }
+
+        let results_df = analysis::search_holdings(&df, &queries, match_mode)?;
+
+        // Print matching holdings to stdout
+        println!("Found {} matching holdings:", number_format.count(results_df.height() as u64));
+        let etfs = results_df.column("ETF")?.str()?;
+        let symbols = results_df.column("Symbol")?.str()?;
+        let names = results_df.column("Name")?.str()?;
+        for i in 0..results_df.height() {
+            println!(
+                "  {}: {} ({})",
+                etfs.get(i).unwrap_or(""),
+                symbols.get(i).unwrap_or(""),
+                names.get(i).unwrap_or("")
+            );
+        }
+
+        // If output file is specified, save with default .csv extension if no extension provided
+        save_output(&args, &results_df, "csv", "search results", &export_options)?;
+
+        return Ok(());
+    }
+
+    // Handle the common-assets function
+    if args.function == "common-assets" {
+        let top = args.top.unwrap_or(20);
+
+        if args.verbose {
+            println!("Finding the {} most commonly held assets...", top);
+        }
+
+        let common_df = analysis::most_common_assets(&df, top)?;
+
+        // Print the ranked assets to stdout
+        println!("Top {} most common assets:", common_df.height());
+        let symbols = common_df.column("Symbol")?.str()?;
+        let names = common_df.column("Name")?.str()?;
+        let counts = common_df.column("ETF_Count")?.u32()?;
+        for i in 0..common_df.height() {
+            println!(
+                "  {} ({}): {} ETFs",
+                symbols.get(i).unwrap_or(""),
+                names.get(i).unwrap_or(""),
+                counts.get(i).unwrap_or(0)
+            );
+        }
+
+        // If output file is specified, save with default .csv extension if no extension provided
+        save_output(&args, &common_df, "csv", "common assets", &export_options)?;
+
+        return Ok(());
+    }
+
+    // Handle the overlap-vs function
+    if args.function == "overlap-vs" {
+        let benchmark = args.benchmark.clone().ok_or_else(|| etf_analyzer::Error::Other(
+            "Overlap-vs function requires --benchmark to be specified".to_string()
+        ))?;
+
+        if args.verbose {
+            println!("Calculating weighted overlap against {}...", benchmark);
+        }
+
+        let overlap_df = analysis::overlap_vs_benchmark(&df, &benchmark)?;
+
+        // Print the ETF-to-overlap table to stdout
+        println!("Weighted overlap vs {}:", benchmark.to_uppercase());
+        let etfs = overlap_df.column("ETF")?.str()?;
+        let overlap = overlap_df.column("Overlap")?.f64()?;
+        for i in 0..overlap_df.height() {
+            println!(
+                "  {}: {}",
+                etfs.get(i).unwrap_or(""),
+                number_format.percent(overlap.get(i).unwrap_or(0.0), 2)
+            );
+        }
+
+        // If output file is specified, save with default .csv extension if no extension provided
+        save_output(&args, &overlap_df, "csv", "benchmark overlap", &export_options)?;
+
+        return Ok(());
+    }
+
+    // Handle the overlap-external function
+    if args.function == "overlap-external" {
+        let reference_path = args.reference.clone().ok_or_else(|| etf_analyzer::Error::Other(
+            "Overlap-external function requires --reference to be specified".to_string()
+        ))?;
+
+        if args.verbose {
+            println!("Loading reference holdings from: {}", reference_path);
+        }
+
+        let reference_df = io::load_holdings_csv_with_config(&reference_path, &column_config)?;
+        let reference_df = if args.no_trim { reference_df } else { io::trim_string_columns(&reference_df)? };
+        let overlap_df = analysis::overlap_with_external_index(&df, &reference_df)?;
+
+        // Print the ETF-to-overlap table to stdout
+        println!("Weighted overlap vs reference {}:", reference_path);
+        let etfs = overlap_df.column("ETF")?.str()?;
+        let overlap = overlap_df.column("Overlap")?.f64()?;
+        for i in 0..overlap_df.height() {
+            println!(
+                "  {}: {}",
+                etfs.get(i).unwrap_or(""),
+                number_format.percent(overlap.get(i).unwrap_or(0.0), 2)
+            );
+        }
+
+        // If output file is specified, save with default .csv extension if no extension provided
+        save_output(&args, &overlap_df, "csv", "reference overlap", &export_options)?;
+
+        return Ok(());
+    }
+
+    // Handle the unique-exposure function
+    if args.function == "unique-exposure" {
+        if args.verbose {
+            println!("Calculating per-ETF unique exposure...");
+        }
+
+        let exposure_df = analysis::unique_exposure_with_context(&ctx)?;
+
+        // Print per-ETF unique and shared weight to stdout
+        println!("Unique exposure by ETF:");
+        let etfs = exposure_df.column("ETF")?.str()?;
+        let unique = exposure_df.column("Unique_Weight")?.f64()?;
+        let shared = exposure_df.column("Shared_Weight")?.f64()?;
+        for i in 0..exposure_df.height() {
+            println!(
+                "  {}: {} unique, {} shared",
+                etfs.get(i).unwrap_or(""),
+                number_format.percent(unique.get(i).unwrap_or(0.0), 2),
+                number_format.percent(shared.get(i).unwrap_or(0.0), 2)
+            );
+        }
+
+        // If output file is specified, save with default .csv extension if no extension provided
+        save_output(&args, &exposure_df, "csv", "unique exposure", &export_options)?;
+
+        return Ok(());
+    }
+
+    // Handle the drawdowns function
+    if args.function == "drawdowns" {
+        // Use the listed price columns, or every numeric column of imported price data
+        // Holdings loaded with -d/--files have no price columns, only No./Shares
+        let price_cols: Vec<String> = match &args.price_cols {
+            Some(cols) => cols.clone(),
+            None if args.import.is_some() => df
+                .get_columns()
+                .iter()
+                .filter(|c| c.dtype().is_numeric() && !HOLDINGS_NUMERIC_COLUMNS.contains(&c.name().as_str()))
+                .map(|c| c.name().to_string())
+                .collect(),
+            None => {
+                return Err(etf_analyzer::Error::Other(
+                    "Drawdowns function requires --price-cols unless price data is imported with -i".to_string()
+                ));
+            }
+        };
+        if price_cols.is_empty() {
+            return Err(etf_analyzer::Error::Other(
+                "Drawdowns function found no numeric price columns (use --price-cols)".to_string()
             ));
         }
 
+        if args.verbose {
+            println!("Calculating max drawdown for {} price column(s)...", price_cols.len());
+        }
+
+        let cols: Vec<&str> = price_cols.iter().map(String::as_str).collect();
+        let drawdowns_df = analysis::max_drawdown_all(&df, &cols)?;
+
+        // Print each column's drawdown to stdout
+        println!("Max drawdown by price column:");
+        let names = drawdowns_df.column("ETF")?.str()?;
+        let drawdowns = drawdowns_df.column("Max_Drawdown")?.f64()?;
+        for i in 0..drawdowns_df.height() {
+            println!(
+                "  {}: {}",
+                names.get(i).unwrap_or(""),
+                number_format.percent(drawdowns.get(i).unwrap_or(0.0), 2)
+            );
+        }
+
+        // If output file is specified, save with default .csv extension if no extension provided
+        save_output(&args, &drawdowns_df, "csv", "drawdowns", &export_options)?;
+
+        return Ok(());
+    }
+
+    // Handle the size-categories function
+    if args.function == "size-categories" {
+        let edges = match args.size_edges.as_deref() {
+            None => analysis::DEFAULT_SIZE_EDGES,
+            Some(&[small, mid, large]) => [small, mid, large],
+            Some(_) => {
+                return Err(etf_analyzer::Error::Other(
+                    "--size-edges takes exactly three values, e.g. 25,100,500".to_string()
+                ));
+            }
+        };
+
+        if args.verbose {
+            println!("Classifying ETFs by holdings count...");
+        }
+
+        let sizes_df = analysis::size_categories(&df, edges)?;
+
+        // Print each ETF's size category to stdout
+        println!("ETF size categories:");
+        let etfs = sizes_df.column("ETF")?.str()?;
+        let holdings = sizes_df.column("Holdings")?.u32()?;
+        let categories = sizes_df.column("Category")?.str()?;
+        for i in 0..sizes_df.height() {
+            println!(
+                "  {}: {} ({} holdings)",
+                etfs.get(i).unwrap_or(""),
+                categories.get(i).unwrap_or(""),
+                holdings.get(i).unwrap_or(0)
+            );
+        }
+
+        // If output file is specified, save with default .csv extension if no extension provided
+        save_output(&args, &sizes_df, "csv", "size categories", &export_options)?;
+
+        return Ok(());
+    }
+
+    // Handle the ubiquity function
+    if args.function == "ubiquity" {
+        let top = args.top.unwrap_or(20);
+
+        if args.verbose {
+            println!("Ranking assets by summed weight across ETFs...");
+        }
+
+        let ubiquity_df = analysis::weighted_ubiquity_with_context(&ctx)?;
+
+        // Print the top assets to stdout
+        println!("Top {} assets by total weight across ETFs:", top.min(ubiquity_df.height()));
+        let symbols = ubiquity_df.column("Symbol")?.str()?;
+        let counts = ubiquity_df.column("ETF_Count")?.u32()?;
+        let totals = ubiquity_df.column("Total_Weight")?.f64()?;
+        for i in 0..ubiquity_df.height().min(top) {
+            println!(
+                "  {}: {} across {} ETF(s)",
+                symbols.get(i).unwrap_or(""),
+                number_format.percent(totals.get(i).unwrap_or(0.0), 2),
+                number_format.count(counts.get(i).unwrap_or(0) as u64)
+            );
+        }
+
+        // If output file is specified, save the full ranking with default .csv extension if no extension provided
+        save_output(&args, &ubiquity_df, "csv", "weighted ubiquity", &export_options)?;
+
+        return Ok(());
+    }
+
+    // Handle the weight-sum function
+    if args.function == "weight-sum" {
+        if args.verbose {
+            println!("Summing weights per ETF...");
+        }
+
+        let sums_df = analysis::weight_sum_with_context(&ctx)?;
+
+        // Print per-ETF weight totals to stdout
+        println!("Weight sum by ETF:");
+        let etfs = sums_df.column("ETF")?.str()?;
+        let sums = sums_df.column("Weight_Sum")?.f64()?;
+        for i in 0..sums_df.height() {
+            println!(
+                "  {}: {}",
+                etfs.get(i).unwrap_or(""),
+                number_format.percent(sums.get(i).unwrap_or(0.0), 2)
+            );
+        }
+
+        // If output file is specified, save with default .csv extension if no extension provided
+        save_output(&args, &sums_df, "csv", "weight sums", &export_options)?;
+
+        return Ok(());
+    }
+
+    // Handle the gini function
+    if args.function == "gini" {
+        if args.verbose {
+            println!("Calculating weight concentration per ETF...");
+        }
+
+        let gini_df = analysis::gini_coefficient_with_context(&ctx)?;
+
+        // Print per-ETF Gini coefficients to stdout
+        println!("Gini coefficient by ETF:");
+        let etfs = gini_df.column("ETF")?.str()?;
+        let gini = gini_df.column("Gini")?.f64()?;
+        for i in 0..gini_df.height() {
+            println!("  {}: {}", etfs.get(i).unwrap_or(""), number_format.number(gini.get(i).unwrap_or(0.0), 4));
+        }
+
+        // If output file is specified, save with default .csv extension if no extension provided
+        save_output(&args, &gini_df, "csv", "Gini coefficients", &export_options)?;
+
+        return Ok(());
+    }
+
+    // Handle the weight-diff function
+    if args.function == "weight-diff" {
+        // Require exactly two ETFs to compare
+        let (etf_a, etf_b) = match args.etfs.as_deref() {
+            Some([a, b]) => (a, b),
+            _ => {
+                return Err(etf_analyzer::Error::Other(
+                    "Weight-diff function requires exactly two ETFs in --etfs (e.g. --etfs SPY,VOO)".to_string()
+                ));
+            }
+        };
+
+        if args.verbose {
+            println!("Comparing weights of {} and {}...", etf_a, etf_b);
+        }
+
+        let diff_df = analysis::weight_diff(&df, etf_a, etf_b)?;
+
+        // Print the weight differences to stdout
+        println!("Weight differences ({} - {}):", etf_a, etf_b);
+        let symbols = diff_df.column("Symbol")?.str()?;
+        let weight_a = diff_df.column("Weight_A")?.f64()?;
+        let weight_b = diff_df.column("Weight_B")?.f64()?;
+        let diffs = diff_df.column("Diff")?.f64()?;
+        for i in 0..diff_df.height() {
+            let diff = diffs.get(i).unwrap_or(0.0);
+            println!(
+                "  {}: {} vs {} ({}{})",
+                symbols.get(i).unwrap_or(""),
+                number_format.percent(weight_a.get(i).unwrap_or(0.0), 2),
+                number_format.percent(weight_b.get(i).unwrap_or(0.0), 2),
+                if diff >= 0.0 { "+" } else { "" },
+                number_format.percent(diff, 2)
+            );
+        }
+
+        // If output file is specified, save with default .csv extension if no extension provided
+        save_output(&args, &diff_df, "csv", "weight differences", &export_options)?;
+
+        return Ok(());
+    }
+
+    // Handle the pair function
+    if args.function == "pair" {
+        // Require exactly two ETFs for the breakdown
+        let (etf_a, etf_b) = match args.etfs.as_deref() {
+            Some([a, b]) => (a, b),
+            _ => {
+                return Err(etf_analyzer::Error::Other(
+                    "Pair function requires exactly two ETFs in --etfs (e.g. --etfs SPY,VOO)".to_string()
+                ));
+            }
+        };
+
+        if args.verbose {
+            println!("Comparing holdings of {} and {}...", etf_a, etf_b);
+        }
+
+        let (only_a, only_b, both) = analysis::pair_breakdown(&df, etf_a, etf_b)?;
+
+        println!("Holdings breakdown:");
+        println!("  Only in {}: {}", etf_a, number_format.count(only_a as u64));
+        println!("  Only in {}: {}", etf_b, number_format.count(only_b as u64));
+        println!("  In both: {}", number_format.count(both as u64));
+
+        return Ok(());
+    }
+
+    // Handle the head and tail functions: show the first/last rows of the loaded data
+    if args.function == "head" || args.function == "tail" {
+        let count = args.top.unwrap_or(10);
+        let rows = if args.function == "head" {
+            df.head(Some(count))
+        } else {
+            df.tail(Some(count))
+        };
+
+        // Polars abbreviates long tables; show every requested row unless the user chose a limit.
+        // The limit is read from the environment at display time, so set it only around the print.
+        // Safe here: no analysis is running and the polars/rayon pool is idle, so no other thread
+        // reads the environment while it changes (the reason set_var is unsafe from edition 2024)
+        if std::env::var_os("POLARS_FMT_MAX_ROWS").is_none() {
+            std::env::set_var("POLARS_FMT_MAX_ROWS", rows.height().to_string());
+            println!("{}", rows);
+            std::env::remove_var("POLARS_FMT_MAX_ROWS");
+        } else {
+            println!("{}", rows);
+        }
+
+        return Ok(());
+    }
+
+    // Handle the jaccard-matrix function
+    if args.function == "jaccard-matrix" {
+        if args.verbose {
+            println!("Calculating Jaccard similarity matrix...");
+        }
+
+        let matrix_df = analysis::jaccard_matrix(&df)?;
+        println!("{}", matrix_df);
+
+        // If output file is specified, save with default .csv extension if no extension provided
+        save_output(&args, &matrix_df, "csv", "Jaccard matrix", &export_options)?;
+
+        return Ok(());
+    }
+
+    // Handle the synthetic function
+    if args.function == "synthetic" {
+        let threshold = args.synthetic_threshold.unwrap_or(analysis::SYNTHETIC_RATIO_THRESHOLD);
+
+        if args.verbose {
+            println!("Counting synthesized symbols per ETF...");
+        }
+
+        let ratios_df = analysis::synthetic_ratio(&df, analysis::is_synthetic_symbol, threshold)?;
+
+        // Print each ETF's synthesized-symbol share to stdout
+        println!("Synthesized symbols by ETF:");
+        let etfs = ratios_df.column("ETF")?.str()?;
+        let totals = ratios_df.column("Total")?.u32()?;
+        let synthetic = ratios_df.column("Synthetic")?.u32()?;
+        let ratios = ratios_df.column("Ratio")?.f64()?;
+        let flagged = ratios_df.column("Flagged")?.bool()?;
+        for i in 0..ratios_df.height() {
+            println!(
+                "  {}: {} of {} ({}){}",
+                etfs.get(i).unwrap_or(""),
+                number_format.count(synthetic.get(i).unwrap_or(0) as u64),
+                number_format.count(totals.get(i).unwrap_or(0) as u64),
+                number_format.percent(ratios.get(i).unwrap_or(0.0), 1),
+                if flagged.get(i).unwrap_or(false) { " [flagged]" } else { "" }
+            );
+        }
+
+        // If output file is specified, save with default .csv extension if no extension provided
+        save_output(&args, &ratios_df, "csv", "synthesized-symbol ratios", &export_options)?;
+
+        return Ok(());
+    }
+
+    // Handle the largest function
+    if args.function == "largest" {
+        if args.verbose {
+            println!("Finding each ETF's largest holding...");
+        }
+
+        let largest_df = analysis::largest_holding_per_etf_with_context(&ctx)?;
+
+        // Print each ETF's biggest position to stdout
+        println!("Largest holding by ETF:");
+        let etfs = largest_df.column("ETF")?.str()?;
+        let symbols = largest_df.column("Symbol")?.str()?;
+        let weights = largest_df.column("Weight")?.f64()?;
+        for i in 0..largest_df.height() {
+            println!(
+                "  {}: {} ({})",
+                etfs.get(i).unwrap_or(""),
+                symbols.get(i).unwrap_or(""),
+                number_format.percent(weights.get(i).unwrap_or(0.0), 2)
+            );
+        }
+
+        // If output file is specified, save with default .csv extension if no extension provided
+        save_output(&args, &largest_df, "csv", "largest holdings", &export_options)?;
+
+        return Ok(());
+    }
+
+    // Handle the outliers function
+    if args.function == "outliers" {
+        let z = args.z.unwrap_or(3.0);
+
+        if args.verbose {
+            println!("Finding holdings with weight z-score above {}...", z);
+        }
+
+        let outliers_df = analysis::weight_outliers_with_context(&ctx, z)?;
+
+        println!("Found {} weight outliers (z-score > {})", number_format.count(outliers_df.height() as u64), z);
+        let etfs = outliers_df.column("ETF")?.str()?;
+        let symbols = outliers_df.column("Symbol")?.str()?;
+        let weights = outliers_df.column("Weight")?.f64()?;
+        let scores = outliers_df.column("ZScore")?.f64()?;
+        for i in 0..outliers_df.height() {
+            println!(
+                "  {} {}: {} (z = {})",
+                etfs.get(i).unwrap_or(""),
+                symbols.get(i).unwrap_or(""),
+                number_format.percent(weights.get(i).unwrap_or(0.0), 2),
+                number_format.number(scores.get(i).unwrap_or(0.0), 2)
+            );
+        }
+
+        // If output file is specified, save with default .csv extension if no extension provided
+        save_output(&args, &outliers_df, "csv", "weight outliers", &export_options)?;
+
         return Ok(());
     }
 
@@ -318,7 +1109,13 @@ fn main() -> Result<()> {
             println!("Getting list of ETFs...");
         }
 
-        let etf_list = analysis::get_etf_list(&df)?;
+        // Keep the load order (e.g. the --files list) instead of sorting if requested
+        let etf_order = if args.stable_etf_order {
+            analysis::ListOrder::Appearance
+        } else {
+            analysis::ListOrder::Sorted
+        };
+        let etf_list = analysis::get_etf_list_with_order(&df, etf_order)?;
 
         // Print to stdout
         println!("Found {} ETFs:", etf_list.len());
@@ -329,11 +1126,7 @@ fn main() -> Result<()> {
         // If output file is specified, save with default .txt extension if no extension provided
         if let Some(output_path) = &args.output {
             // Add .txt extension if no extension is present
-            let output_path_with_ext = if std::path::Path::new(output_path).extension().is_none() {
-                format!("{}.txt", output_path)
-            } else {
-                output_path.to_string()
-            };
+            let output_path_with_ext = with_default_extension(output_path, "txt");
 
             if args.verbose {
                 println!("Saving ETF list to: {}", output_path_with_ext);
@@ -341,54 +1134,143 @@ fn main() -> Result<()> {
 
             // Write ETF list to text file (one per line)
             let content = etf_list.join("\n") + "\n";
-            std::fs::write(&output_path_with_ext, content)?;
-            println!("ETF list saved to: {}", output_path_with_ext);
+            check_output_path(&args, &output_path_with_ext)?;
+            if io::write_text_with_options(&output_path_with_ext, &content, &export_options)? {
+                println!("ETF list saved to: {}", output_path_with_ext);
+            }
         }
 
         return Ok(());
     }
 
-    // Extract unique ETF names from the "ETF" column
-    let etf_names: Vec<String> = df
-        .column("ETF")
-        .ok()
-        .and_then(|col| col.str().ok())
-        .map(|s| {
-            s.into_iter()
-                .flatten()
-                .collect::<std::collections::HashSet<_>>()
-                .into_iter()
-                .map(|s| s.to_string())
-                .collect()
-        })
-        .unwrap_or_else(|| vec!["Unknown".to_string()]);
+    // Every known function returns above; --function is validated before loading
+    Err(unknown_function_error(&args.function))
+}
 
-    if args.verbose {
-        println!("Found {} ETFs: {}", etf_names.len(), etf_names.join(", "));
-        println!("{}", report::generate_dataframe_summary(&df)?);
+/// Error for a --function value that isn't in cli::FUNCTIONS
+fn unknown_function_error(function: &str) -> etf_analyzer::Error {
+    etf_analyzer::Error::Other(format!(
+        "Unknown function '{}'. Valid functions: {}",
+        function,
+        cli::FUNCTIONS.join(", ")
+    ))
+}
+
+/// Print how much of the dataset a filter kept, in rows and ETFs
+fn print_filter_report(before: &DataFrame, after: &DataFrame, number_format: &locale::NumberFormat) -> Result<()> {
+    let retained = if before.height() == 0 {
+        0.0
+    } else {
+        after.height() as f64 / before.height() as f64
+    };
+
+    println!(
+        "Filtered from {} to {} rows ({} retained), from {} to {} ETFs",
+        number_format.count(before.height() as u64),
+        number_format.count(after.height() as u64),
+        number_format.percent(retained, 1),
+        analysis::get_etf_list(before)?.len(),
+        analysis::get_etf_list(after)?.len()
+    );
+
+    Ok(())
+}
+
+/// Append `.ext` to a path that has no extension
+fn with_default_extension(path: &str, ext: &str) -> String {
+    if std::path::Path::new(path).extension().is_none() {
+        format!("{}.{}", path, ext)
+    } else {
+        path.to_string()
     }
+}
 
-    // Create a Portfolio struct to manage state
-    let mut portfolio = portfolio::Portfolio::new(etf_names);
-    portfolio.load_data(df)?;
+/// Save a function's result to --output, adding `.default_ext` when the path has no extension
+/// `label` names the result in messages ("Saving {label} to", "{Label} saved to")
+/// Returns the written path, or None without --output or when the user declined to overwrite
+fn save_output(
+    args: &cli::Args,
+    df: &DataFrame,
+    default_ext: &str,
+    label: &str,
+    export_options: &io::ExportOptions,
+) -> Result<Option<String>> {
+    let Some(output_path) = &args.output else {
+        return Ok(None);
+    };
+    let output_path = with_default_extension(output_path, default_ext);
 
     if args.verbose {
-        println!("{}", portfolio.summary());
+        println!("Saving {} to: {}", label, output_path);
+    }
+    if !write_output(args, df, &output_path, export_options)? {
+        return Ok(None);
     }
 
-    // Generate output
-    if let Some(output_path) = &args.output {
-        if args.verbose {
-            println!("Saving results to: {}", output_path);
+    let mut chars = label.chars();
+    let label = chars.next().map(|c| c.to_uppercase().chain(chars).collect::<String>()).unwrap_or_default();
+    println!("{} saved to: {}", label, output_path);
+
+    Ok(Some(output_path))
+}
+
+/// Check the output path and export, honouring the overwrite options; returns false if the user declined
+fn write_output(args: &cli::Args, df: &DataFrame, output_path: &str, export_options: &io::ExportOptions) -> Result<bool> {
+    check_output_path(args, output_path)?;
+    io::export_dataframe_with_options(df, output_path, export_options)
+}
+
+/// Warn (or fail under --strict) when an output file would be picked up as holdings on the next run
+fn check_output_path(args: &cli::Args, output_path: &str) -> Result<()> {
+    let Some(data_dir) = &args.data_dir else {
+        return Ok(());
+    };
+
+    if io::is_holdings_file_in_dir(output_path, data_dir) {
+        let message = format!(
+            "Output '{}' is inside the data directory and matches the *-etf-holdings.csv pattern; it will be loaded as holdings on the next run",
+            output_path
+        );
+        if args.strict {
+            return Err(etf_analyzer::Error::Other(message));
+        }
+        eprintln!("Warning: {}", message);
+    }
+
+    Ok(())
+}
+
+/// Load the configuration file from the default locations and print a pass/fail list
+fn validate_config() -> Result<()> {
+    let cfg = match config::Config::load_default() {
+        Ok(Some(cfg)) => cfg,
+        Ok(None) => {
+            println!("No configuration file found");
+            return Ok(());
         }
-        if let Some(data) = &portfolio.data {
-            io::save_csv(data, output_path)?;
+        Err(e) => {
+            println!("[FAIL] parse: {}", e);
+            return Err(etf_analyzer::Error::Other(
+                "Configuration file is invalid".to_string()
+            ));
         }
+    };
+
+    println!("[PASS] parse: configuration file is well-formed");
+
+    let checks = cfg.validate();
+    for check in &checks {
+        let status = if check.passed { "PASS" } else { "FAIL" };
+        println!("[{}] {}: {}", status, check.name, check.message);
     }
 
-    if args.verbose {
-        println!("ETF Analyzer finished.");
+    let failures = checks.iter().filter(|c| !c.passed).count();
+    if failures > 0 {
+        return Err(etf_analyzer::Error::Other(
+            format!("Configuration has {} issue(s)", failures)
+        ));
     }
 
+    println!("Configuration is valid");
     Ok(())
 }
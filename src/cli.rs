@@ -1,5 +1,41 @@
 use clap::Parser;
 
+/// Names accepted by --function
+pub const FUNCTIONS: &[&str] = &[
+    "summary",
+    "list",
+    "assets",
+    "unique",
+    "overlap",
+    "compare",
+    "mapping",
+    "profile",
+    "avg-overlap",
+    "overlap-pairs",
+    "overlap-vs",
+    "unique-exposure",
+    "outliers",
+    "jaccard-matrix",
+    "pair",
+    "weight-sum",
+    "common-assets",
+    "search",
+    "portfolio-state",
+    "largest",
+    "weight-diff",
+    "overlap-external",
+    "size-categories",
+    "synthetic",
+    "drawdowns",
+    "portfolio-return",
+    "ubiquity",
+    "gini",
+    "head",
+    "tail",
+    "export",
+    "validate-config",
+];
+
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 pub struct Args {
@@ -11,7 +47,15 @@ pub struct Args {
     #[arg(short = 'i', long)]
     pub import: Option<String>,
 
-    /// Function/operation to perform (summary, list, assets, unique, overlap, compare, mapping, export)
+    /// Comma-separated list of holdings files to load instead of scanning a directory
+    #[arg(long, value_delimiter = ',')]
+    pub files: Option<Vec<String>>,
+
+    /// Text file listing holdings files to load, one path per line
+    #[arg(long)]
+    pub files_from: Option<String>,
+
+    /// Function/operation to perform (summary, list, assets, unique, overlap, compare, mapping, profile, avg-overlap, overlap-pairs, overlap-vs, unique-exposure, outliers, jaccard-matrix, pair, weight-sum, common-assets, search, portfolio-state, largest, weight-diff, overlap-external, size-categories, synthetic, drawdowns, portfolio-return, ubiquity, gini, head, tail, export, validate-config)
     #[arg(short = 'f', long, default_value = "summary")]
     pub function: String,
 
@@ -19,14 +63,106 @@ pub struct Args {
     #[arg(short, long)]
     pub output: Option<String>,
 
+    /// Also write the assets function's ETF_Count distribution as CSV to this path
+    #[arg(long)]
+    pub dist_output: Option<String>,
+
+    /// Append a Weight_Pct column (parsed weight formatted like "5.00%") when using the export function
+    #[arg(long)]
+    pub add_pct_column: bool,
+
+    /// Append a Rank_In_ETF column (position within the holding's ETF by weight) when using the export function
+    #[arg(long)]
+    pub add_rank_column: bool,
+
+    /// Token written for null cells in CSV output (default: empty field)
+    #[arg(long)]
+    pub null_value: Option<String>,
+
     /// Force overwrite of existing output files without prompting
     #[arg(long)]
     pub force: bool,
 
+    /// Overwrite existing output files of these formats without prompting (csv, csv.gz, parquet)
+    #[arg(long, alias = "assume-yes-for-format", value_delimiter = ',')]
+    pub force_format: Option<Vec<String>>,
+
+    /// Never overwrite existing output files of these formats; answer No without prompting (--force still overwrites)
+    #[arg(long, alias = "assume-no-for-format", value_delimiter = ',')]
+    pub keep_format: Option<Vec<String>>,
+
+    /// Treat warnings as errors (e.g. writing holdings-pattern output into the data directory)
+    #[arg(long)]
+    pub strict: bool,
+
     /// Sort order for assets function: 'symbol' (alphabetical) or 'count' (by ETF count)
     #[arg(long, default_value = "symbol")]
     pub sort_by: String,
 
+    /// Assets function: only include assets held by at least this many ETFs
+    #[arg(long)]
+    pub min_etfs: Option<u32>,
+
+    /// Assets function: only include assets held by at most this many ETFs
+    #[arg(long)]
+    pub max_etfs: Option<u32>,
+
+    /// Compare function: only include assets held by at least this many of the compared ETFs
+    #[arg(long)]
+    pub min_present: Option<usize>,
+
+    /// Order of comma-separated ETF/asset lists: 'sorted' (alphabetical) or 'appearance' (input order)
+    #[arg(long, default_value = "sorted")]
+    pub list_order: String,
+
+    /// Weight aggregation for assets function: 'sum', 'mean', 'max', or 'none' (no weight column)
+    #[arg(long, default_value = "none")]
+    pub asset_weight_agg: String,
+
+    /// Number of results to show for ranked functions (overlap-pairs default: 10, common-assets default: 20, ubiquity default: 20) and rows for head/tail (default: 10)
+    #[arg(long)]
+    pub top: Option<usize>,
+
+    /// Benchmark ETF for the overlap-vs function (e.g., VTI)
+    #[arg(long)]
+    pub benchmark: Option<String>,
+
+    /// Reference holdings file (e.g. index constituents) for the overlap-external function
+    #[arg(long)]
+    pub reference: Option<String>,
+
+    /// Z-score threshold for the outliers function (default: 3.0)
+    #[arg(long)]
+    pub z: Option<f64>,
+
+    /// Holdings-count boundaries for size-categories as SMALL,MID,LARGE (default: 25,100,500)
+    #[arg(long, value_delimiter = ',')]
+    pub size_edges: Option<Vec<usize>>,
+
+    /// Synthetic function: flag ETFs whose share of synthesized symbols exceeds this ratio (default: 0.2)
+    #[arg(long)]
+    pub synthetic_threshold: Option<f64>,
+
+    /// Drawdowns function: comma-separated price columns (default: every numeric column)
+    #[arg(long, value_delimiter = ',')]
+    pub price_cols: Option<Vec<String>>,
+
+    /// Portfolio-return function: CSV of per-ETF returns with ETF and Return columns
+    #[arg(long)]
+    pub returns_file: Option<String>,
+
+    /// Output format for portfolio-state (text or json)
+    #[arg(long, default_value = "text")]
+    pub format: String,
+
+    /// Search function: terms to find in Symbol or Name (repeat the flag or use a comma list)
+    #[arg(long, value_delimiter = ',')]
+    pub query: Option<Vec<String>>,
+
+    /// Search function: match rows containing any term (any) or every term (all)
+    #[arg(long = "match", default_value = "any")]
+    pub match_mode: String,
+
     /// Comma-separated list of ETF symbols to include in analysis (e.g., VTI,VOO,SPY)
     #[arg(long, value_delimiter = ',')]
     pub etfs: Option<Vec<String>>,
@@ -51,11 +187,155 @@ pub struct Args {
     #[arg(long)]
     pub number_col: Option<String>,
 
+    /// Column name for the integer part of a split weight (used together with --weight-frac-col)
+    #[arg(long)]
+    pub weight_whole_col: Option<String>,
+
+    /// Column name for the fractional digits of a split weight (used together with --weight-whole-col)
+    #[arg(long)]
+    pub weight_frac_col: Option<String>,
+
+    /// Rename output columns as old:new pairs, e.g. "Symbol:Ticker,Weight:% of Assets"
+    #[arg(long, alias = "rename-output-columns", value_delimiter = ',')]
+    pub rename_out: Option<Vec<String>>,
+
+    /// Write the ETFs/Assets list columns as Parquet lists instead of comma-joined strings (CSV output is unchanged)
+    #[arg(long)]
+    pub list_as_array: bool,
+
+    /// Compression for Parquet output: snappy, zstd, gzip or none
+    #[arg(long, default_value = "snappy")]
+    pub parquet_compression: String,
+
+    /// Gzip CSV output (writes FILE.csv.gz)
+    #[arg(long)]
+    pub gzip: bool,
+
+    /// Locale for numbers in summaries and printed tables, e.g. de-DE for "1.234,56" (default: C, plain numbers)
+    #[arg(long)]
+    pub locale: Option<String>,
+
+    /// Output filename template used when --output is not given, e.g. "{function}_{etfs}_{date}.csv"
+    #[arg(long)]
+    pub output_template: Option<String>,
+
+    /// Summary and assets functions: also write the summary statistics to <output>.summary.json
+    #[arg(long)]
+    pub with_summary_json: bool,
+
+    /// List ETFs in load order (e.g. as given by --files / --files-from) instead of alphabetically
+    #[arg(long)]
+    pub stable_etf_order: bool,
+
+    /// Drop rows whose Symbol was synthesized as {ETF}-{No.} (keep real tickers only)
+    #[arg(long)]
+    pub exclude_synthetic: bool,
+
+    /// Exclude ETFs with fewer than this many holdings from analysis
+    #[arg(long)]
+    pub min_holdings: Option<usize>,
+
+    /// Keep leading/trailing whitespace in Symbol, Name and ETF values (trimmed by default)
+    #[arg(long)]
+    pub no_trim: bool,
+
+    /// Make a bare Enter at the overwrite prompt mean Yes (default is No)
+    #[arg(long)]
+    pub default_yes: bool,
+
+    /// Write sample holdings files (ALPHA, BETA, GAMMA) into this directory and exit
+    #[arg(long, value_name = "DIR")]
+    pub generate_sample: Option<String>,
+
+    /// Print version and build information as JSON and exit
+    #[arg(long)]
+    pub build_info: bool,
+
     /// Verbose mode
     #[arg(short, long)]
     pub verbose: bool,
 }
 
+/// Polars version the crate is built against, read from Cargo.lock by build.rs ("unknown" without a lock file)
+pub const POLARS_VERSION: &str = env!("POLARS_VERSION");
+
+/// Cargo features enabled in this build
+pub fn enabled_features() -> Vec<&'static str> {
+    // No optional features are defined yet; add cfg!(feature = "...") checks here
+    Vec::new()
+}
+
+/// Build information as a JSON object, for bug reports
+pub fn build_info_json() -> String {
+    let features = enabled_features()
+        .iter()
+        .map(|f| crate::report::json_string(f))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!(
+        "{{\"version\": {}, \"polars_version\": {}, \"features\": [{}], \"target\": {}}}",
+        crate::report::json_string(env!("CARGO_PKG_VERSION")),
+        crate::report::json_string(POLARS_VERSION),
+        features,
+        crate::report::json_string(env!("TARGET"))
+    )
+}
+
+/// Expand an output filename template such as "{function}_{etfs}_{date}.csv"
+/// `{etfs}` is the ETF list joined with "-" ("all" when no filter is given); `{date}` is YYYY-MM-DD
+pub fn expand_output_template(
+    template: &str,
+    function: &str,
+    etfs: Option<&[String]>,
+    date: &str,
+) -> crate::Result<String> {
+    let etfs = match etfs {
+        Some(etfs) if !etfs.is_empty() => etfs.join("-"),
+        _ => "all".to_string(),
+    };
+
+    let expanded = template
+        .replace("{function}", function)
+        .replace("{etfs}", &etfs)
+        .replace("{date}", date);
+
+    let has_file_name = std::path::Path::new(&expanded)
+        .file_name()
+        .is_some_and(|name| !name.to_string_lossy().trim().is_empty());
+    if expanded.trim().is_empty() || expanded.ends_with(['/', '\\']) || !has_file_name {
+        return Err(crate::Error::Other(
+            format!("Output template '{}' does not produce a file name", template)
+        ));
+    }
+
+    Ok(expanded)
+}
+
+/// Today's date (UTC) as YYYY-MM-DD, for the {date} output template placeholder
+pub fn today() -> String {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Convert days since 1970-01-01 to a (year, month, day) calendar date
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
 pub fn parse_args() -> Args {
     Args::parse()
 }
@@ -69,4 +349,24 @@ mod tests {
         use clap::CommandFactory;
         Args::command().debug_assert();
     }
+
+    #[test]
+    fn test_expand_output_template() {
+        let etfs = vec!["SPY".to_string(), "VOO".to_string()];
+        let name = expand_output_template("{function}_{etfs}_{date}.csv", "overlap", Some(&etfs), "2024-03-01").unwrap();
+        assert_eq!(name, "overlap_SPY-VOO_2024-03-01.csv");
+
+        let name = expand_output_template("out/{function}_{etfs}.csv", "assets", None, "2024-03-01").unwrap();
+        assert_eq!(name, "out/assets_all.csv");
+
+        assert!(expand_output_template("", "assets", None, "2024-03-01").is_err());
+        assert!(expand_output_template("out/", "assets", None, "2024-03-01").is_err());
+    }
+
+    #[test]
+    fn test_civil_from_days() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+        assert_eq!(today().len(), 10);
+    }
 }
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use std::fs;
 use crate::Result;
@@ -25,9 +26,17 @@ pub struct Config {
     /// Force overwrite without prompting
     pub force: Option<bool>,
 
+    /// Default answer Yes at the overwrite prompt
+    pub default_yes: Option<bool>,
+
     /// Verbose mode
     pub verbose: Option<bool>,
 
+    /// Per-format overwrite policy: `csv = true` overwrites CSV files without prompting,
+    /// `parquet = false` keeps existing Parquet files without prompting
+    #[serde(default)]
+    pub overwrite: BTreeMap<String, bool>,
+
     /// Column name overrides
     #[serde(default)]
     pub columns: ColumnConfig,
@@ -41,6 +50,8 @@ pub struct ColumnConfig {
     pub weight_col: Option<String>,
     pub shares_col: Option<String>,
     pub number_col: Option<String>,
+    pub weight_whole_col: Option<String>,
+    pub weight_frac_col: Option<String>,
 }
 
 impl Default for ColumnConfig {
@@ -51,6 +62,26 @@ impl Default for ColumnConfig {
             weight_col: None,
             shares_col: None,
             number_col: None,
+            weight_whole_col: None,
+            weight_frac_col: None,
+        }
+    }
+}
+
+/// Outcome of a single configuration check
+#[derive(Debug, Clone)]
+pub struct ConfigCheck {
+    pub name: String,
+    pub passed: bool,
+    pub message: String,
+}
+
+impl ConfigCheck {
+    fn new(name: &str, passed: bool, message: String) -> Self {
+        Self {
+            name: name.to_string(),
+            passed,
+            message,
         }
     }
 }
@@ -114,6 +145,81 @@ impl Config {
             .map(PathBuf::from)
     }
 
+    /// Check the configuration for common mistakes
+    /// Only settings that are present are checked; returns one entry per check performed
+    pub fn validate(&self) -> Vec<ConfigCheck> {
+        let mut checks = Vec::new();
+
+        if let Some(data_dir) = &self.data_dir {
+            let path = Path::new(data_dir);
+            checks.push(if path.is_dir() {
+                ConfigCheck::new("data_dir", true, format!("directory exists: {}", data_dir))
+            } else {
+                ConfigCheck::new("data_dir", false, format!("directory does not exist: {}", data_dir))
+            });
+        }
+
+        if let Some(function) = &self.function {
+            let known = crate::cli::FUNCTIONS.contains(&function.as_str());
+            checks.push(if function == "validate-config" {
+                // As a default it would replace every run that doesn't pass -f
+                ConfigCheck::new("function", false,
+                    "validate-config cannot be the default function (run it with -f validate-config)".to_string())
+            } else if known {
+                ConfigCheck::new("function", true, format!("known function: {}", function))
+            } else {
+                ConfigCheck::new("function", false, format!(
+                    "unknown function '{}' (expected one of: {})",
+                    function,
+                    crate::cli::FUNCTIONS.join(", ")
+                ))
+            });
+        }
+
+        if let Some(sort_by) = &self.sort_by {
+            let parsed = crate::analysis::AssetsSortBy::try_from_str(sort_by).is_some();
+            checks.push(if parsed {
+                ConfigCheck::new("sort_by", true, format!("valid sort order: {}", sort_by))
+            } else {
+                ConfigCheck::new("sort_by", false, format!(
+                    "unknown sort order '{}' (expected 'symbol' or 'count')", sort_by
+                ))
+            });
+        }
+
+        for format in self.overwrite.keys() {
+            let name = format!("overwrite.{}", format);
+            checks.push(if crate::io::FileFormat::try_from_name(format).is_some() {
+                ConfigCheck::new(&name, true, format!("known format: {}", format))
+            } else {
+                ConfigCheck::new(&name, false, format!(
+                    "unknown format '{}' (expected csv, csv.gz or parquet)", format
+                ))
+            });
+        }
+
+        let column_overrides = [
+            ("columns.symbol_col", &self.columns.symbol_col),
+            ("columns.name_col", &self.columns.name_col),
+            ("columns.weight_col", &self.columns.weight_col),
+            ("columns.shares_col", &self.columns.shares_col),
+            ("columns.number_col", &self.columns.number_col),
+            ("columns.weight_whole_col", &self.columns.weight_whole_col),
+            ("columns.weight_frac_col", &self.columns.weight_frac_col),
+        ];
+        for (name, value) in column_overrides {
+            if let Some(value) = value {
+                checks.push(if value.trim().is_empty() {
+                    ConfigCheck::new(name, false, "column name is empty".to_string())
+                } else {
+                    ConfigCheck::new(name, true, format!("column name: {}", value))
+                });
+            }
+        }
+
+        checks
+    }
+
     /// Merge config with CLI arguments, giving CLI arguments priority
     pub fn merge_with_cli(&self, cli_args: &mut crate::cli::Args) {
         // Only set from config if CLI arg is None/default
@@ -144,6 +250,27 @@ impl Config {
             cli_args.force = true;
         }
 
+        // [overwrite] answers the overwrite prompt per format: true is Yes, false is No
+        let policy = |answer: bool| -> Option<Vec<String>> {
+            let formats: Vec<String> = self
+                .overwrite
+                .iter()
+                .filter(|(_, &overwrite)| overwrite == answer)
+                .map(|(format, _)| format.clone())
+                .collect();
+            (!formats.is_empty()).then_some(formats)
+        };
+        if cli_args.force_format.is_none() {
+            cli_args.force_format = policy(true);
+        }
+        if cli_args.keep_format.is_none() {
+            cli_args.keep_format = policy(false);
+        }
+
+        if !cli_args.default_yes && self.default_yes == Some(true) {
+            cli_args.default_yes = true;
+        }
+
         if !cli_args.verbose && self.verbose == Some(true) {
             cli_args.verbose = true;
         }
@@ -164,6 +291,12 @@ impl Config {
         if cli_args.number_col.is_none() {
             cli_args.number_col = self.columns.number_col.clone();
         }
+        if cli_args.weight_whole_col.is_none() {
+            cli_args.weight_whole_col = self.columns.weight_whole_col.clone();
+        }
+        if cli_args.weight_frac_col.is_none() {
+            cli_args.weight_frac_col = self.columns.weight_frac_col.clone();
+        }
     }
 }
 
@@ -193,6 +326,25 @@ mod tests {
         assert_eq!(config.columns.weight_col, Some("Weighting".to_string()));
     }
 
+    #[test]
+    fn test_overwrite_table_sets_force_format() {
+        let toml_str = r#"
+            [overwrite]
+            csv = true
+            parquet = false
+        "#;
+
+        let config: Config = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.overwrite.get("csv"), Some(&true));
+
+        use clap::Parser;
+        let mut args = crate::cli::Args::parse_from(["etf_analyzer"]);
+        config.merge_with_cli(&mut args);
+        assert_eq!(args.force_format, Some(vec!["csv".to_string()]));
+        assert_eq!(args.keep_format, Some(vec!["parquet".to_string()]));
+        assert!(!args.force);
+    }
+
     #[test]
     fn test_default_config() {
         let config = Config::default();
@@ -226,6 +378,47 @@ mod tests {
             weight_col: None,
             shares_col: None,
             number_col: None,
+            weight_whole_col: None,
+            weight_frac_col: None,
+            asset_weight_agg: "none".to_string(),
+            add_pct_column: false,
+            strict: false,
+            null_value: None,
+            top: None,
+            files: None,
+            files_from: None,
+            z: None,
+            list_order: "sorted".to_string(),
+            benchmark: None,
+            dist_output: None,
+            min_etfs: None,
+            max_etfs: None,
+            build_info: false,
+            default_yes: false,
+            no_trim: false,
+            min_holdings: None,
+            stable_etf_order: false,
+            with_summary_json: false,
+            exclude_synthetic: false,
+            query: None,
+            match_mode: "any".to_string(),
+            format: "text".to_string(),
+            output_template: None,
+            reference: None,
+            parquet_compression: "snappy".to_string(),
+            gzip: false,
+            generate_sample: None,
+            size_edges: None,
+            min_present: None,
+            synthetic_threshold: None,
+            rename_out: None,
+            price_cols: None,
+            list_as_array: false,
+            returns_file: None,
+            add_rank_column: false,
+            force_format: None,
+            locale: None,
+            keep_format: None,
         };
 
         config.merge_with_cli(&mut args);
@@ -260,6 +453,47 @@ mod tests {
             weight_col: None,
             shares_col: None,
             number_col: None,
+            weight_whole_col: None,
+            weight_frac_col: None,
+            asset_weight_agg: "none".to_string(),
+            add_pct_column: false,
+            strict: false,
+            null_value: None,
+            top: None,
+            files: None,
+            files_from: None,
+            z: None,
+            list_order: "sorted".to_string(),
+            benchmark: None,
+            dist_output: None,
+            min_etfs: None,
+            max_etfs: None,
+            build_info: false,
+            default_yes: false,
+            no_trim: false,
+            min_holdings: None,
+            stable_etf_order: false,
+            with_summary_json: false,
+            exclude_synthetic: false,
+            query: None,
+            match_mode: "any".to_string(),
+            format: "text".to_string(),
+            output_template: None,
+            reference: None,
+            parquet_compression: "snappy".to_string(),
+            gzip: false,
+            generate_sample: None,
+            size_edges: None,
+            min_present: None,
+            synthetic_threshold: None,
+            rename_out: None,
+            price_cols: None,
+            list_as_array: false,
+            returns_file: None,
+            add_rank_column: false,
+            force_format: None,
+            locale: None,
+            keep_format: None,
         };
 
         config.merge_with_cli(&mut args);
@@ -282,6 +516,8 @@ mod tests {
                 name_col: Some("CompanyName".to_string()),
                 shares_col: None,
                 number_col: None,
+                weight_whole_col: None,
+                weight_frac_col: None,
             },
             ..Default::default()
         };
@@ -300,6 +536,47 @@ mod tests {
             weight_col: None,
             shares_col: None,
             number_col: None,
+            weight_whole_col: None,
+            weight_frac_col: None,
+            asset_weight_agg: "none".to_string(),
+            add_pct_column: false,
+            strict: false,
+            null_value: None,
+            top: None,
+            files: None,
+            files_from: None,
+            z: None,
+            list_order: "sorted".to_string(),
+            benchmark: None,
+            dist_output: None,
+            min_etfs: None,
+            max_etfs: None,
+            build_info: false,
+            default_yes: false,
+            no_trim: false,
+            min_holdings: None,
+            stable_etf_order: false,
+            with_summary_json: false,
+            exclude_synthetic: false,
+            query: None,
+            match_mode: "any".to_string(),
+            format: "text".to_string(),
+            output_template: None,
+            reference: None,
+            parquet_compression: "snappy".to_string(),
+            gzip: false,
+            generate_sample: None,
+            size_edges: None,
+            min_present: None,
+            synthetic_threshold: None,
+            rename_out: None,
+            price_cols: None,
+            list_as_array: false,
+            returns_file: None,
+            add_rank_column: false,
+            force_format: None,
+            locale: None,
+            keep_format: None,
         };
 
         config.merge_with_cli(&mut args);
@@ -337,6 +614,47 @@ mod tests {
             weight_col: None,
             shares_col: None,
             number_col: None,
+            weight_whole_col: None,
+            weight_frac_col: None,
+            asset_weight_agg: "none".to_string(),
+            add_pct_column: false,
+            strict: false,
+            null_value: None,
+            top: None,
+            files: None,
+            files_from: None,
+            z: None,
+            list_order: "sorted".to_string(),
+            benchmark: None,
+            dist_output: None,
+            min_etfs: None,
+            max_etfs: None,
+            build_info: false,
+            default_yes: false,
+            no_trim: false,
+            min_holdings: None,
+            stable_etf_order: false,
+            with_summary_json: false,
+            exclude_synthetic: false,
+            query: None,
+            match_mode: "any".to_string(),
+            format: "text".to_string(),
+            output_template: None,
+            reference: None,
+            parquet_compression: "snappy".to_string(),
+            gzip: false,
+            generate_sample: None,
+            size_edges: None,
+            min_present: None,
+            synthetic_threshold: None,
+            rename_out: None,
+            price_cols: None,
+            list_as_array: false,
+            returns_file: None,
+            add_rank_column: false,
+            force_format: None,
+            locale: None,
+            keep_format: None,
         };
 
         config.merge_with_cli(&mut args);
@@ -368,4 +686,34 @@ mod tests {
         let config: Config = toml::from_str(toml_str).unwrap();
         assert_eq!(config.etfs, Some(vec!["VTI".to_string(), "VOO".to_string(), "SPY".to_string()]));
     }
+
+    #[test]
+    fn test_validate_reports_failures() {
+        let config = Config {
+            data_dir: Some("./does_not_exist_dir".to_string()),
+            function: Some("bogus".to_string()),
+            sort_by: Some("count".to_string()),
+            columns: ColumnConfig {
+                symbol_col: Some("  ".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let checks = config.validate();
+        let failed: Vec<&str> = checks.iter()
+            .filter(|c| !c.passed)
+            .map(|c| c.name.as_str())
+            .collect();
+
+        assert_eq!(failed, vec!["data_dir", "function", "columns.symbol_col"]);
+        assert!(checks.iter().any(|c| c.name == "sort_by" && c.passed));
+
+        let config = Config {
+            function: Some("validate-config".to_string()),
+            ..Default::default()
+        };
+        let checks = config.validate();
+        assert!(checks.iter().any(|c| c.name == "function" && !c.passed));
+    }
 }
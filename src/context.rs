@@ -0,0 +1,114 @@
+use polars::prelude::*;
+use std::path::Path;
+use crate::analysis::{self, WeightParser};
+use crate::io::{self, ColumnConfig};
+use crate::Result;
+
+/// Loaded holdings data bundled with the column config used to load it and its parsed weights
+/// Build it once and run several analyses off the same state (see the `*_with_context` analyses)
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnalyzerContext {
+    pub data: DataFrame,
+    pub columns: ColumnConfig,
+    pub weight_parser: WeightParser,
+    /// Weight column parsed as fractions, one entry per row; None when there is no Weight column
+    weights: Option<Vec<Option<f64>>>,
+}
+
+impl AnalyzerContext {
+    /// Create a context from an already loaded DataFrame, parsing its Weight column once
+    pub fn new(data: DataFrame, columns: ColumnConfig) -> Result<Self> {
+        let weight_parser = WeightParser;
+        let weights = if data.column("Weight").is_ok() {
+            Some(weight_parser.values(&data)?)
+        } else {
+            None
+        };
+
+        Ok(AnalyzerContext {
+            data,
+            columns,
+            weight_parser,
+            weights,
+        })
+    }
+
+    /// Create a context for a DataFrame loaded with the default column config
+    /// Used by the `&DataFrame` analyses to delegate to their context versions
+    pub fn from_frame(df: &DataFrame) -> Result<Self> {
+        Self::new(df.clone(), ColumnConfig::default())
+    }
+
+    /// Load all holdings files from a directory into a new context
+    pub fn from_directory<P: AsRef<Path>>(dir_path: P, columns: ColumnConfig) -> Result<Self> {
+        let data = io::load_portfolio_from_directory_with_config(dir_path, &columns)?;
+        Self::new(data, columns)
+    }
+
+    /// Return a new context restricted to the given ETFs
+    pub fn filter_etfs(&self, etfs: &[String]) -> Result<Self> {
+        Self::new(analysis::filter_etfs(&self.data, etfs)?, self.columns.clone())
+    }
+
+    /// Parsed Weight values as fractions, one per row of `data`
+    /// Returns a Parse error naming `function` when the data has no Weight column
+    pub fn weights(&self, function: &str) -> Result<&[Option<f64>]> {
+        analysis::require_weight_column(&self.data, function)?;
+        Ok(self.weights.as_deref().unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_context() -> AnalyzerContext {
+        let df = df! {
+            "ETF" => &["SPY", "QQQ", "SPY", "IWF"],
+            "Symbol" => &["AAPL", "AAPL", "MSFT", "GOOGL"],
+            "Name" => &["Apple", "Apple", "Microsoft", "Google"],
+            "Weight" => &["5%", "6%", "7%", "8%"]
+        }.unwrap();
+
+        AnalyzerContext::new(df, ColumnConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn test_context_runs_multiple_analyses() {
+        let ctx = sample_context();
+
+        let sums = analysis::weight_sum_with_context(&ctx).unwrap();
+        let sums = sums.column("Weight_Sum").unwrap().f64().unwrap();
+        assert!((sums.get(2).unwrap() - 0.12).abs() < 1e-9);
+
+        let exposure = analysis::unique_exposure_with_context(&ctx).unwrap();
+        let shared = exposure.column("Shared_Weight").unwrap().f64().unwrap();
+        assert!((shared.get(1).unwrap() - 0.06).abs() < 1e-9);
+
+        // Running analyses does not modify the shared state, and the free functions agree
+        assert_eq!(ctx, sample_context());
+        assert!(analysis::unique_exposure(&ctx.data).unwrap().equals(&exposure));
+    }
+
+    #[test]
+    fn test_context_filter_etfs() {
+        let ctx = sample_context();
+        let filtered = ctx.filter_etfs(&["SPY".to_string()]).unwrap();
+
+        assert_eq!(filtered.weights("test").unwrap(), &[Some(0.05), Some(0.07)]);
+        assert_eq!(filtered.columns, ctx.columns);
+        assert_ne!(filtered, ctx);
+    }
+
+    #[test]
+    fn test_context_without_weights() {
+        let df = df! {
+            "ETF" => &["SPY"],
+            "Symbol" => &["AAPL"]
+        }.unwrap();
+        let ctx = AnalyzerContext::new(df, ColumnConfig::default()).unwrap();
+
+        let err = analysis::gini_coefficient_with_context(&ctx).unwrap_err();
+        assert!(err.to_string().contains("Gini coefficient"));
+    }
+}
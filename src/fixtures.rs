@@ -0,0 +1,100 @@
+use std::path::{Path, PathBuf};
+use crate::io::ExportOptions;
+use crate::Result;
+
+/// ETFs and holdings counts written by --generate-sample
+pub const SAMPLE_ETFS: &[(&str, usize)] = &[("ALPHA", 10), ("BETA", 15), ("GAMMA", 30)];
+
+/// Tickers used by sample_holdings_csv, in order
+const SAMPLE_ASSETS: &[(&str, &str)] = &[
+    ("AAPL", "Apple Inc."),
+    ("MSFT", "Microsoft Corporation"),
+    ("NVDA", "NVIDIA Corporation"),
+    ("AMZN", "Amazon.com Inc."),
+    ("GOOGL", "Alphabet Inc. Class A"),
+    ("META", "Meta Platforms Inc."),
+    ("BRK.B", "Berkshire Hathaway Inc. Class B"),
+    ("AVGO", "Broadcom Inc."),
+    ("TSLA", "Tesla Inc."),
+    ("JPM", "JPMorgan Chase & Co."),
+    ("LLY", "Eli Lilly and Company"),
+    ("V", "Visa Inc. Class A"),
+    ("XOM", "Exxon Mobil Corporation"),
+    ("UNH", "UnitedHealth Group Inc."),
+    ("MA", "Mastercard Inc. Class A"),
+    ("COST", "Costco Wholesale Corporation"),
+    ("HD", "Home Depot Inc."),
+    ("PG", "Procter & Gamble Company"),
+    ("JNJ", "Johnson & Johnson"),
+    ("WMT", "Walmart Inc."),
+];
+
+/// Sample holdings CSV for `etf` with the standard columns (No., Symbol, Name, % Weight, Shares)
+/// and `rows` equally weighted holdings.
+/// Output is deterministic: holdings are taken in order from a fixed ticker list,
+/// continuing with `{ETF}-SAMPLE-{n}` symbols once the list runs out.
+pub fn sample_holdings_csv(etf: &str, rows: usize) -> String {
+    let mut content = String::from("No.,Symbol,Name,% Weight,Shares\n");
+    let weight = if rows > 0 { 100.0 / rows as f64 } else { 0.0 };
+    for i in 0..rows {
+        let (symbol, name) = match SAMPLE_ASSETS.get(i) {
+            Some((symbol, name)) => (symbol.to_string(), name.to_string()),
+            None => (
+                format!("{}-SAMPLE-{}", etf.to_uppercase(), i + 1),
+                format!("{} Sample Holding {}", etf.to_uppercase(), i + 1),
+            ),
+        };
+        content.push_str(&format!(
+            "{},{},\"{}\",{:.4}%,{}\n",
+            i + 1,
+            symbol,
+            name,
+            weight,
+            (rows - i) * 1_000
+        ));
+    }
+    content
+}
+
+/// Write sample_holdings_csv as `{etf}-etf-holdings.csv` into `dir`
+/// Existing files get the usual overwrite prompt (skipped by `options.force`)
+/// Returns the path written, or None if the user declined to overwrite
+pub fn write_sample_holdings<P: AsRef<Path>>(
+    dir: P,
+    etf: &str,
+    rows: usize,
+    options: &ExportOptions,
+) -> Result<Option<PathBuf>> {
+    if etf.trim().is_empty() {
+        return Err(crate::Error::Other("Sample ETF name must not be empty".to_string()));
+    }
+
+    let dir = dir.as_ref();
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(format!("{}-etf-holdings.csv", etf.to_lowercase()));
+
+    let written = crate::io::write_text_with_options(&path, &sample_holdings_csv(etf, rows), options)?;
+    Ok(written.then_some(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_sample_holdings_loads() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let options = ExportOptions::default();
+        let path = write_sample_holdings(temp_dir.path(), "aaa", 5, &options).unwrap().unwrap();
+        assert_eq!(path.file_name().unwrap(), "aaa-etf-holdings.csv");
+        write_sample_holdings(temp_dir.path(), "BBB", 25, &options).unwrap();
+
+        let df = crate::io::load_portfolio_from_directory(temp_dir.path()).unwrap();
+        assert_eq!(df.height(), 30);
+        assert_eq!(crate::analysis::get_etf_list(&df).unwrap(), vec!["AAA", "BBB"]);
+
+        // Rows past the fixed ticker list get ETF-specific symbols
+        let symbols = df.column("Symbol").unwrap().str().unwrap();
+        assert!(symbols.into_iter().flatten().any(|s| s == "BBB-SAMPLE-25"));
+    }
+}
@@ -1,4 +1,5 @@
 use polars::prelude::*;
+use std::collections::HashMap;
 use std::path::Path;
 use std::fs;
 use std::io::{self, Write};
@@ -6,13 +7,17 @@ use rayon::prelude::*;
 use crate::Result;
 
 /// Configuration for column name mapping
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct ColumnConfig {
     pub symbol_col: String,
     pub name_col: String,
     pub weight_col: String,
     pub shares_col: String,
     pub number_col: String,
+    /// Optional column holding the integer part of a split weight (e.g. "Weight_Whole")
+    pub weight_whole_col: Option<String>,
+    /// Optional column holding the fractional digits of a split weight (e.g. "Weight_Frac")
+    pub weight_frac_col: Option<String>,
 }
 
 impl Default for ColumnConfig {
@@ -23,11 +28,18 @@ impl Default for ColumnConfig {
             weight_col: "% Weight".to_string(),
             shares_col: "Shares".to_string(),
             number_col: "No.".to_string(),
+            weight_whole_col: None,
+            weight_frac_col: None,
         }
     }
 }
 
 impl ColumnConfig {
+    /// Start building a ColumnConfig; unset columns keep their defaults
+    pub fn builder() -> ColumnConfigBuilder {
+        ColumnConfigBuilder::default()
+    }
+
     /// Create a ColumnConfig from CLI arguments
     pub fn from_args(
         symbol_col: Option<String>,
@@ -35,6 +47,8 @@ impl ColumnConfig {
         weight_col: Option<String>,
         shares_col: Option<String>,
         number_col: Option<String>,
+        weight_whole_col: Option<String>,
+        weight_frac_col: Option<String>,
     ) -> Self {
         let default = Self::default();
         Self {
@@ -43,10 +57,62 @@ impl ColumnConfig {
             weight_col: weight_col.unwrap_or(default.weight_col),
             shares_col: shares_col.unwrap_or(default.shares_col),
             number_col: number_col.unwrap_or(default.number_col),
+            weight_whole_col,
+            weight_frac_col,
         }
     }
 }
 
+/// Builder for ColumnConfig, e.g. `ColumnConfig::builder().symbol("Ticker").build()`
+#[derive(Clone, Debug, Default)]
+pub struct ColumnConfigBuilder {
+    config: ColumnConfig,
+}
+
+impl ColumnConfigBuilder {
+    /// Set the symbol column name
+    pub fn symbol(mut self, name: impl Into<String>) -> Self {
+        self.config.symbol_col = name.into();
+        self
+    }
+
+    /// Set the name column name
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.config.name_col = name.into();
+        self
+    }
+
+    /// Set the weight column name
+    pub fn weight(mut self, name: impl Into<String>) -> Self {
+        self.config.weight_col = name.into();
+        self
+    }
+
+    /// Set the shares column name
+    pub fn shares(mut self, name: impl Into<String>) -> Self {
+        self.config.shares_col = name.into();
+        self
+    }
+
+    /// Set the row number column name
+    pub fn number(mut self, name: impl Into<String>) -> Self {
+        self.config.number_col = name.into();
+        self
+    }
+
+    /// Set the columns holding the integer and fractional parts of a split weight
+    pub fn split_weight(mut self, whole: impl Into<String>, frac: impl Into<String>) -> Self {
+        self.config.weight_whole_col = Some(whole.into());
+        self.config.weight_frac_col = Some(frac.into());
+        self
+    }
+
+    /// Finish building the ColumnConfig
+    pub fn build(self) -> ColumnConfig {
+        self.config
+    }
+}
+
 /// Load ETF data from a CSV file
 pub fn load_csv<P: AsRef<Path>>(path: P) -> Result<DataFrame> {
     let df = CsvReadOptions::default()
@@ -58,13 +124,45 @@ pub fn load_csv<P: AsRef<Path>>(path: P) -> Result<DataFrame> {
 
 /// Save DataFrame to CSV file
 pub fn save_csv<P: AsRef<Path>>(df: &DataFrame, path: P) -> Result<()> {
+    save_csv_with_null_value(df, path, "")
+}
+
+/// Save DataFrame to CSV file, writing null cells as `null_value` (empty by default)
+/// List columns (ETFs/Assets) are written as comma-separated strings
+pub fn save_csv_with_null_value<P: AsRef<Path>>(df: &DataFrame, path: P, null_value: &str) -> Result<()> {
     let mut file = std::fs::File::create(path)?;
     CsvWriter::new(&mut file)
-        .finish(&mut df.clone())?;
+        .with_null_value(null_value.to_string())
+        .finish(&mut crate::analysis::join_list_columns(df)?)?;
 
     Ok(())
 }
 
+/// Save DataFrame to a gzip-compressed CSV file, writing null cells as `null_value`
+pub fn save_csv_gz_with_null_value<P: AsRef<Path>>(df: &DataFrame, path: P, null_value: &str) -> Result<()> {
+    let file = std::fs::File::create(path)?;
+    let mut encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+    CsvWriter::new(&mut encoder)
+        .with_null_value(null_value.to_string())
+        .finish(&mut crate::analysis::join_list_columns(df)?)?;
+    encoder.finish()?;
+
+    Ok(())
+}
+
+/// Load a gzip-compressed CSV file
+pub fn load_csv_gz<P: AsRef<Path>>(path: P) -> Result<DataFrame> {
+    let file = std::fs::File::open(path)?;
+    let mut bytes = Vec::new();
+    io::Read::read_to_end(&mut flate2::read::GzDecoder::new(file), &mut bytes)?;
+
+    let df = CsvReadOptions::default()
+        .into_reader_with_file_handle(std::io::Cursor::new(bytes))
+        .finish()?;
+
+    Ok(df)
+}
+
 /// Save DataFrame to JSON file (requires "json" feature)
 /// For now, this saves as CSV. Enable "json" feature in Cargo.toml to use JSON output.
 pub fn save_json<P: AsRef<Path>>(df: &DataFrame, path: P) -> Result<()> {
@@ -80,18 +178,73 @@ fn extract_etf_name<P: AsRef<Path>>(path: P) -> Result<String> {
         .and_then(|f| f.to_str())
         .ok_or_else(|| crate::Error::Parse("Invalid filename".to_string()))?;
 
-    // Remove .csv extension
-    let name_without_ext = filename.strip_suffix(".csv").unwrap_or(filename);
+    // Remove .csv extension (case-insensitive)
+    let name_without_ext = strip_suffix_ignore_case(filename, ".csv").unwrap_or(filename);
 
     // Extract ETF name from pattern: {etf_name}-etf-holdings
-    if let Some(etf_name) = name_without_ext.strip_suffix("-etf-holdings") {
-        Ok(etf_name.to_uppercase())
-    } else {
+    // Only the final "-etf-holdings" is removed, so names containing "etf" or hyphens are kept intact
+    match strip_suffix_ignore_case(name_without_ext, "-etf-holdings") {
+        Some(etf_name) if !etf_name.is_empty() => Ok(etf_name.to_uppercase()),
         // Fallback: use filename without extension
-        Ok(name_without_ext.to_uppercase())
+        _ => Ok(name_without_ext.to_uppercase()),
     }
 }
 
+/// Whether a file name matches the *-etf-holdings.csv pattern, ignoring case like extract_etf_name
+fn is_holdings_filename(name: &str) -> bool {
+    strip_suffix_ignore_case(name, "-etf-holdings.csv").is_some()
+}
+
+/// Strip an ASCII suffix, ignoring case
+fn strip_suffix_ignore_case<'a>(s: &'a str, suffix: &str) -> Option<&'a str> {
+    let split = s.len().checked_sub(suffix.len())?;
+    if s.is_char_boundary(split) && s[split..].eq_ignore_ascii_case(suffix) {
+        Some(&s[..split])
+    } else {
+        None
+    }
+}
+
+/// Combine a whole-number column and a fractional-digits column into a Weight column
+/// whole=5, frac=25 becomes "5.25%", matching the percent strings of a regular weight column
+/// The two source columns are dropped from the result
+fn combine_weight_columns(df: DataFrame, whole_col: &str, frac_col: &str) -> Result<DataFrame> {
+    for name in [whole_col, frac_col] {
+        if df.column(name).is_err() {
+            return Err(crate::Error::Parse(
+                format!("Weight column '{}' not found", name)
+            ));
+        }
+    }
+
+    // A numeric fraction has already lost leading zeros ("05" read as 5), so it can't be combined safely
+    if df.column(frac_col)?.dtype() != &DataType::String {
+        return Err(crate::Error::Parse(format!(
+            "Weight fraction column '{}' must be read as text to keep leading zeros",
+            frac_col
+        )));
+    }
+
+    let whole_values = df.column(whole_col)?.cast(&DataType::String)?;
+    let frac_values = df.column(frac_col)?.cast(&DataType::String)?;
+
+    let combined: Vec<Option<String>> = whole_values
+        .str()?
+        .into_iter()
+        .zip(frac_values.str()?)
+        .map(|(whole, frac)| match (whole, frac) {
+            (Some(w), Some(f)) => Some(format!("{}.{}%", w.trim(), f.trim())),
+            (Some(w), None) => Some(format!("{}%", w.trim())),
+            _ => None,
+        })
+        .collect();
+
+    let mut df = df.drop(whole_col)?.drop(frac_col)?;
+    df.with_column(Series::new("Weight".into(), combined))?;
+
+    Ok(df)
+}
+
 /// Load ETF holdings CSV file with configurable column names
 /// The function will:
 /// - Rename columns from user-specified names to standard names (Symbol, Name, Weight)
@@ -110,8 +263,18 @@ pub fn load_holdings_csv_with_config<P: AsRef<Path>>(
     let path_ref = path.as_ref();
     let etf_name = extract_etf_name(path_ref)?;
 
+    // Read split weight columns as strings so digits like a "05" fraction come through verbatim
+    let mut read_options = CsvReadOptions::default();
+    if let (Some(whole_col), Some(frac_col)) = (&config.weight_whole_col, &config.weight_frac_col) {
+        let schema = Schema::from_iter([
+            Field::new(whole_col.as_str().into(), DataType::String),
+            Field::new(frac_col.as_str().into(), DataType::String),
+        ]);
+        read_options = read_options.with_schema_overwrite(Some(std::sync::Arc::new(schema)));
+    }
+
     // Load CSV
-    let mut df = CsvReadOptions::default()
+    let mut df = read_options
         .try_into_reader_with_file_path(Some(path_ref.to_path_buf()))?
         .finish()?;
 
@@ -140,6 +303,11 @@ pub fn load_holdings_csv_with_config<P: AsRef<Path>>(
         df.rename(&config.shares_col, "Shares".into())?;
     }
 
+    // Combine split weight columns into a single Weight column when both are configured
+    if let (Some(whole_col), Some(frac_col)) = (&config.weight_whole_col, &config.weight_frac_col) {
+        df = combine_weight_columns(df, whole_col, frac_col)?;
+    }
+
     // Synthesize Symbol values for empty/null/n/a entries
     if let Some(no_series) = &no_col {
         if df.column("Symbol").is_ok() {
@@ -152,12 +320,13 @@ pub fn load_holdings_csv_with_config<P: AsRef<Path>>(
                 .zip(no_str.into_iter())
                 .map(|(symbol, no)| {
                     match symbol {
-                        Some(s) if !s.is_empty() && s.to_lowercase() != "n/a" => {
+                        // Whitespace-only symbols count as blank; trimming later would leave them as ""
+                        Some(s) if !s.trim().is_empty() && s.trim().to_lowercase() != "n/a" => {
                             Some(s.to_string())
                         }
                         _ => {
-                            // Synthesize: {ETF}-{No}
-                            no.map(|n| format!("{}-{}", etf_name, n))
+                            // Synthesize: {ETF}-{No}; a float-typed No. column reads as "1.0"
+                            no.map(|n| format!("{}-{}", etf_name, n.strip_suffix(".0").unwrap_or(n)))
                         }
                     }
                 })
@@ -200,6 +369,31 @@ pub fn load_holdings_csv_with_config<P: AsRef<Path>>(
     Ok(df)
 }
 
+/// Trim leading/trailing whitespace from the Symbol, Name and ETF columns
+/// Padded values like "AAPL  " would otherwise group separately from "AAPL"
+pub fn trim_string_columns(df: &DataFrame) -> Result<DataFrame> {
+    let mut result = df.clone();
+
+    for name in ["ETF", "Symbol", "Name"] {
+        let Ok(column) = df.column(name) else {
+            continue;
+        };
+        if column.dtype() != &DataType::String {
+            continue;
+        }
+
+        let trimmed: Vec<Option<String>> = column
+            .str()?
+            .into_iter()
+            .map(|value| value.map(|s| s.trim().to_string()))
+            .collect();
+
+        result.replace(name, Series::new(name.into(), trimmed))?;
+    }
+
+    Ok(result)
+}
+
 /// Load multiple ETF holdings files and combine them into a single DataFrame
 /// Uses parallel processing with Rayon for improved performance when loading many files
 pub fn load_multiple_holdings<P: AsRef<Path> + Send + Sync>(paths: Vec<P>) -> Result<DataFrame> {
@@ -243,6 +437,72 @@ pub fn load_multiple_holdings_with_config<P: AsRef<Path> + Send + Sync>(
     Ok(combined)
 }
 
+/// Load an explicit list of ETF holdings files, bypassing directory scanning
+/// ETF names still come from the filenames; errors if any listed file is missing
+pub fn load_holdings_files_with_config<P: AsRef<Path> + Send + Sync>(
+    paths: Vec<P>,
+    config: &ColumnConfig,
+) -> Result<DataFrame> {
+    let missing: Vec<String> = paths
+        .iter()
+        .filter(|path| !path.as_ref().is_file())
+        .map(|path| path.as_ref().display().to_string())
+        .collect();
+
+    if !missing.is_empty() {
+        return Err(crate::Error::Other(
+            format!("Holdings file(s) not found: {}", missing.join(", "))
+        ));
+    }
+
+    load_multiple_holdings_with_config(paths, config)
+}
+
+/// Read a list of file paths, one per line
+/// Blank lines and lines starting with '#' are ignored
+pub fn read_file_list<P: AsRef<Path>>(path: P) -> Result<Vec<String>> {
+    let content = fs::read_to_string(path.as_ref())?;
+
+    let paths = content
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.to_string())
+        .collect();
+
+    Ok(paths)
+}
+
+/// Read per-ETF returns from a CSV file with ETF and Return columns
+/// Returns may be fractions (0.05) or percentages ("5%"); ETF symbols are uppercased
+pub fn read_returns_file<P: AsRef<Path>>(path: P) -> Result<HashMap<String, f64>> {
+    let df = load_csv(path.as_ref())?;
+
+    let etfs = df.column("ETF")?.str()?;
+    let return_col = df.column("Return")?;
+    let values: Vec<Option<f64>> = match return_col.dtype() {
+        DataType::String => return_col
+            .str()?
+            .into_iter()
+            .map(|opt| opt.and_then(crate::analysis::parse_weight))
+            .collect(),
+        _ => return_col.cast(&DataType::Float64)?.f64()?.into_iter().collect(),
+    };
+
+    let mut returns = HashMap::new();
+    for (etf, value) in etfs.into_iter().zip(values) {
+        let Some(etf) = etf.map(str::trim).filter(|s| !s.is_empty()) else {
+            continue;
+        };
+        let value = value.ok_or_else(|| crate::Error::Parse(
+            format!("Invalid return for ETF {} in {}", etf, path.as_ref().display())
+        ))?;
+        returns.insert(etf.to_uppercase(), value);
+    }
+
+    Ok(returns)
+}
+
 /// Load all ETF holdings CSV files from a directory
 /// Looks for files matching pattern: *-etf-holdings.csv
 pub fn load_portfolio_from_directory<P: AsRef<Path>>(dir_path: P) -> Result<DataFrame> {
@@ -275,10 +535,9 @@ pub fn load_portfolio_from_directory_with_config<P: AsRef<Path>>(
         .map(|entry| entry.path())
         .filter(|path| {
             path.is_file() &&
-            path.extension().map_or(false, |ext| ext == "csv") &&
             path.file_name()
                 .and_then(|name| name.to_str())
-                .map_or(false, |name| name.ends_with("-etf-holdings.csv"))
+                .is_some_and(is_holdings_filename)
         })
         .collect();
 
@@ -294,24 +553,82 @@ pub fn load_portfolio_from_directory_with_config<P: AsRef<Path>>(
     load_multiple_holdings_with_config(csv_files, config)
 }
 
+/// Check whether `path` is inside `dir_path` and matches the *-etf-holdings.csv pattern,
+/// i.e. whether load_portfolio_from_directory would pick it up as an input file
+pub fn is_holdings_file_in_dir<P: AsRef<Path>, Q: AsRef<Path>>(path: P, dir_path: Q) -> bool {
+    let path = path.as_ref();
+
+    let matches_pattern = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(is_holdings_filename);
+    if !matches_pattern {
+        return false;
+    }
+
+    // Canonicalize the parent since the output file itself may not exist yet
+    let parent = match path.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p,
+        _ => Path::new("."),
+    };
+
+    match (fs::canonicalize(parent), fs::canonicalize(dir_path.as_ref())) {
+        (Ok(parent), Ok(dir)) => parent == dir,
+        _ => false,
+    }
+}
+
 /// Determine file format from extension
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum FileFormat {
     Csv,
+    CsvGz,
     Parquet,
 }
 
+/// Whether a path ends in .csv.gz (only gzipped CSV is supported; e.g. .parquet.gz is not)
+pub fn is_csv_gz<P: AsRef<Path>>(path: P) -> bool {
+    path.as_ref()
+        .file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.to_lowercase().ends_with(".csv.gz"))
+}
+
 impl FileFormat {
     pub fn from_path<P: AsRef<Path>>(path: P) -> Self {
-        path.as_ref()
-            .extension()
+        let path = path.as_ref();
+        path.extension()
             .and_then(|ext| ext.to_str())
             .map(|ext| match ext.to_lowercase().as_str() {
                 "parquet" | "pq" => FileFormat::Parquet,
+                "gz" if is_csv_gz(path) => FileFormat::CsvGz,
                 _ => FileFormat::Csv,
             })
             .unwrap_or(FileFormat::Parquet) // Default to Parquet if no extension
     }
+
+    /// Formats named by a user-facing format or extension, as used by --force-format
+    /// "csv" covers gzipped CSV too; returns None for unknown names
+    pub fn try_from_name(name: &str) -> Option<Vec<Self>> {
+        match name.trim().trim_start_matches('.').to_lowercase().as_str() {
+            "csv" => Some(vec![FileFormat::Csv, FileFormat::CsvGz]),
+            "csv.gz" | "gz" => Some(vec![FileFormat::CsvGz]),
+            "parquet" | "pq" => Some(vec![FileFormat::Parquet]),
+            _ => None,
+        }
+    }
+
+    /// Parse a list of format names, failing on the first unknown one
+    pub fn parse_names(names: &[String]) -> Result<Vec<Self>> {
+        let mut formats = Vec::new();
+        for name in names {
+            let parsed = Self::try_from_name(name).ok_or_else(|| crate::Error::Parse(
+                format!("Unknown file format '{}' (expected csv, csv.gz or parquet)", name)
+            ))?;
+            formats.extend(parsed);
+        }
+        Ok(formats)
+    }
 }
 
 /// Import DataFrame from file (auto-detects CSV or Parquet based on extension)
@@ -356,6 +673,7 @@ pub fn import_dataframe<P: AsRef<Path>>(path: P) -> Result<DataFrame> {
 
     match FileFormat::from_path(&actual_path) {
         FileFormat::Csv => load_csv(&actual_path),
+        FileFormat::CsvGz => load_csv_gz(&actual_path),
         FileFormat::Parquet => {
             let file = std::fs::File::open(&actual_path)?;
             let df = ParquetReader::new(file).finish()?;
@@ -364,38 +682,271 @@ pub fn import_dataframe<P: AsRef<Path>>(path: P) -> Result<DataFrame> {
     }
 }
 
+/// Append a Weight_Pct column with the parsed Weight formatted as a percentage (e.g. "5.00%")
+/// Cells whose weight cannot be parsed are left null
+pub fn add_weight_pct_column(df: &DataFrame) -> Result<DataFrame> {
+    let pct_values: Vec<Option<String>> = crate::analysis::weight_values(df)?
+        .into_iter()
+        .map(|w| w.map(|w| format!("{:.2}%", w * 100.0)))
+        .collect();
+
+    let mut result = df.clone();
+    result.with_column(Series::new("Weight_Pct".into(), pct_values))?;
+
+    Ok(result)
+}
+
+/// Counts of the cell formats found in a weight column
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WeightColumnReport {
+    /// Cells like "5.25%"
+    pub percent: usize,
+    /// Cells like "$1,234.56" (values rather than weights)
+    pub currency: usize,
+    /// Cells like "0.0525"
+    pub numeric: usize,
+    /// Non-empty cells that match none of the above
+    pub unparseable: usize,
+    /// Null or empty cells
+    pub null: usize,
+}
+
+impl WeightColumnReport {
+    /// One-line description of the counts
+    pub fn summary(&self) -> String {
+        format!(
+            "{} percent, {} currency, {} numeric, {} unparseable, {} null",
+            self.percent, self.currency, self.numeric, self.unparseable, self.null
+        )
+    }
+}
+
+/// Classify the cells of a weight column as percent, currency, bare numeric or unparseable
+/// Diagnostic only: shows what WeightParser will see before analysis runs
+pub fn inspect_weight_column(df: &DataFrame, col: &str) -> Result<WeightColumnReport> {
+    let column = df.column(col)?;
+    let mut report = WeightColumnReport::default();
+
+    if column.dtype() != &DataType::String {
+        report.null = column.null_count();
+        report.numeric = column.len() - report.null;
+        return Ok(report);
+    }
+
+    for cell in column.str()?.into_iter() {
+        let Some(cell) = cell.map(str::trim).filter(|s| !s.is_empty()) else {
+            report.null += 1;
+            continue;
+        };
+
+        if cell.ends_with('%') && crate::analysis::parse_weight(cell).is_some() {
+            report.percent += 1;
+        } else if cell.contains('$') && cell.replace(['$', ','], "").trim().parse::<f64>().is_ok() {
+            report.currency += 1;
+        } else if cell.parse::<f64>().is_ok() {
+            report.numeric += 1;
+        } else {
+            report.unparseable += 1;
+        }
+    }
+
+    Ok(report)
+}
+
+/// Compression codec for Parquet exports
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ParquetCodec {
+    #[default]
+    Snappy,
+    Zstd,
+    Gzip,
+    None,
+}
+
+impl ParquetCodec {
+    /// Parse a codec name (snappy, zstd, gzip, none); returns None for unknown names
+    pub fn try_from_str(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "snappy" => Some(ParquetCodec::Snappy),
+            "zstd" => Some(ParquetCodec::Zstd),
+            "gzip" => Some(ParquetCodec::Gzip),
+            "none" | "uncompressed" => Some(ParquetCodec::None),
+            _ => None,
+        }
+    }
+
+    /// The matching Polars compression setting, at the codec's default level
+    pub fn to_polars(self) -> ParquetCompression {
+        match self {
+            ParquetCodec::Snappy => ParquetCompression::Snappy,
+            ParquetCodec::Zstd => ParquetCompression::Zstd(None),
+            ParquetCodec::Gzip => ParquetCompression::Gzip(None),
+            ParquetCodec::None => ParquetCompression::Uncompressed,
+        }
+    }
+}
+
+/// Ask before overwriting an existing file unless `forced`
+/// Returns false (after printing "Export cancelled.") if the user declined
+pub fn confirm_overwrite(path: &Path, forced: bool, default_yes: bool) -> Result<bool> {
+    if !path.exists() || forced {
+        return Ok(true);
+    }
+
+    let choices = if default_yes { "[Y/n]" } else { "[y/N]" };
+    print!("File '{}' already exists. Overwrite? {}: ", path.display(), choices);
+    io::stdout().flush()?;
+
+    let confirmed = read_overwrite_answer(&mut io::stdin().lock(), default_yes)?;
+    if !confirmed {
+        println!("Export cancelled.");
+    }
+    Ok(confirmed)
+}
+
+/// Read one answer to the overwrite prompt
+/// A bare Enter picks `default_yes`; end of input (e.g. a closed stdin under cron) always means No
+fn read_overwrite_answer<R: io::BufRead>(reader: &mut R, default_yes: bool) -> Result<bool> {
+    let mut response = String::new();
+    if reader.read_line(&mut response)? == 0 {
+        return Ok(false);
+    }
+
+    let response = response.trim().to_lowercase();
+    Ok(if response.is_empty() {
+        default_yes
+    } else {
+        response == "y" || response == "yes"
+    })
+}
+
+/// Write a text report (e.g. the ETF list or portfolio state) with the same overwrite prompt as exports
+/// Only --force skips the prompt; --force-format names data formats, not text reports
+/// Returns false if the user declined to overwrite
+pub fn write_text_with_options<P: AsRef<Path>>(path: P, content: &str, options: &ExportOptions) -> Result<bool> {
+    let path_ref = path.as_ref();
+    if !confirm_overwrite(path_ref, options.force, options.default_yes)? {
+        return Ok(false);
+    }
+    std::fs::write(path_ref, content)?;
+    Ok(true)
+}
+
+/// Options controlling how export_dataframe writes files
+#[derive(Clone, Debug, Default)]
+pub struct ExportOptions {
+    /// Overwrite existing files without prompting
+    pub force: bool,
+    /// Overwrite existing files of these formats without prompting (a per-format --force)
+    pub force_formats: Vec<FileFormat>,
+    /// Keep existing files of these formats without prompting (a per-format "No"); forcing wins
+    pub keep_formats: Vec<FileFormat>,
+    /// Treat a bare Enter at the overwrite prompt as Yes instead of No
+    pub default_yes: bool,
+    /// Token written for null cells in CSV output (empty when None)
+    pub null_value: Option<String>,
+    /// Compression for Parquet output (CSV is gzipped when the path ends in .gz)
+    pub parquet_compression: ParquetCodec,
+    /// Output column renames as (old, new) pairs, applied just before writing
+    pub rename: Vec<(String, String)>,
+    /// Keep the ETFs/Assets columns as List(String) in Parquet output instead of joining them (CSV always joins)
+    pub list_as_array: bool,
+}
+
+/// Parse `old:new` column rename specs (as given to --rename-out) into (old, new) pairs
+pub fn parse_column_renames(specs: &[String]) -> Result<Vec<(String, String)>> {
+    specs
+        .iter()
+        .map(|spec| match spec.split_once(':') {
+            Some((old, new)) if !old.trim().is_empty() && !new.trim().is_empty() => {
+                Ok((old.trim().to_string(), new.trim().to_string()))
+            }
+            _ => Err(crate::Error::Parse(
+                format!("Invalid column rename '{}' (expected old:new)", spec)
+            )),
+        })
+        .collect()
+}
+
+/// Rename output columns, failing if any old name is not a column of `df`
+pub fn rename_columns(df: &DataFrame, renames: &[(String, String)]) -> Result<DataFrame> {
+    let mut renamed = df.clone();
+    for (old, new) in renames {
+        if renamed.column(old).is_err() {
+            return Err(crate::Error::Other(format!(
+                "Cannot rename column '{}': output columns are {:?}",
+                old,
+                df.get_column_names()
+            )));
+        }
+        renamed.rename(old, new.as_str().into())?;
+    }
+    Ok(renamed)
+}
+
 /// Export DataFrame to file (auto-detects CSV or Parquet based on extension)
 /// Returns true if file was written, false if user cancelled overwrite
 pub fn export_dataframe<P: AsRef<Path>>(
     df: &DataFrame,
     path: P,
     force: bool,
+) -> Result<bool> {
+    let options = ExportOptions {
+        force,
+        ..Default::default()
+    };
+    export_dataframe_with_options(df, path, &options)
+}
+
+/// Export DataFrame to file with custom export options
+/// Returns true if file was written, false if user cancelled overwrite
+pub fn export_dataframe_with_options<P: AsRef<Path>>(
+    df: &DataFrame,
+    path: P,
+    options: &ExportOptions,
 ) -> Result<bool> {
     let path_ref = path.as_ref();
 
-    // Check if file exists and prompt for overwrite unless --force is specified
-    if path_ref.exists() && !force {
-        print!("File '{}' already exists. Overwrite? [y/N]: ", path_ref.display());
-        io::stdout().flush()?;
+    // Apply output column renames first so a bad mapping fails before any prompt
+    let renamed;
+    let df = if options.rename.is_empty() {
+        df
+    } else {
+        renamed = rename_columns(df, &options.rename)?;
+        &renamed
+    };
 
-        let mut response = String::new();
-        io::stdin().read_line(&mut response)?;
+    let format = FileFormat::from_path(path_ref);
 
-        let response = response.trim().to_lowercase();
-        if response != "y" && response != "yes" {
-            println!("Export cancelled.");
-            return Ok(false);
-        }
+    // Check if file exists and prompt for overwrite unless --force (or --force-format) covers it
+    let forced = options.force || options.force_formats.contains(&format);
+    if path_ref.exists() && !forced && options.keep_formats.contains(&format) {
+        println!("File '{}' already exists and its format is set to never overwrite. Export cancelled.", path_ref.display());
+        return Ok(false);
+    }
+    if !confirm_overwrite(path_ref, forced, options.default_yes)? {
+        return Ok(false);
     }
 
-    match FileFormat::from_path(path_ref) {
+    match format {
         FileFormat::Csv => {
-            save_csv(df, path_ref)?;
+            let null_value = options.null_value.as_deref().unwrap_or("");
+            save_csv_with_null_value(df, path_ref, null_value)?;
+        }
+        FileFormat::CsvGz => {
+            let null_value = options.null_value.as_deref().unwrap_or("");
+            save_csv_gz_with_null_value(df, path_ref, null_value)?;
         }
         FileFormat::Parquet => {
+            let mut df = if options.list_as_array {
+                df.clone()
+            } else {
+                crate::analysis::join_list_columns(df)?
+            };
             let file = std::fs::File::create(path_ref)?;
             ParquetWriter::new(file)
-                .finish(&mut df.clone())?;
+                .with_compression(options.parquet_compression.to_polars())
+                .finish(&mut df)?;
         }
     }
 
@@ -406,6 +957,17 @@ pub fn export_dataframe<P: AsRef<Path>>(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_read_overwrite_answer() {
+        assert!(read_overwrite_answer(&mut "\n".as_bytes(), true).unwrap());
+        assert!(!read_overwrite_answer(&mut "\n".as_bytes(), false).unwrap());
+        assert!(read_overwrite_answer(&mut "Yes\n".as_bytes(), false).unwrap());
+        assert!(!read_overwrite_answer(&mut "n\n".as_bytes(), true).unwrap());
+
+        // EOF is never a Yes, even when a bare Enter would be
+        assert!(!read_overwrite_answer(&mut io::empty(), true).unwrap());
+    }
+
     #[test]
     fn test_load_csv_validates_path() {
         let result = load_csv("nonexistent.csv");
@@ -421,6 +983,21 @@ mod tests {
         assert_eq!(result, "VOO");
     }
 
+    #[test]
+    fn test_read_returns_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("returns.csv");
+        std::fs::write(&path, "ETF,Return\nspy,8%\nQQQ,0.2\n").unwrap();
+
+        let returns = read_returns_file(&path).unwrap();
+        assert_eq!(returns.len(), 2);
+        assert!((returns["SPY"] - 0.08).abs() < 1e-9);
+        assert!((returns["QQQ"] - 0.2).abs() < 1e-9);
+
+        std::fs::write(&path, "ETF,Return\nSPY,n/a\n").unwrap();
+        assert!(read_returns_file(&path).is_err());
+    }
+
     #[test]
     fn test_extract_etf_name_fallback() {
         let result = extract_etf_name("other-file.csv").unwrap();
@@ -433,10 +1010,421 @@ mod tests {
         assert_eq!(result, "QQQ");
     }
 
+    #[test]
+    fn test_extract_etf_name_multi_hyphen() {
+        let result = extract_etf_name("all-world-etf-holdings.csv").unwrap();
+        assert_eq!(result, "ALL-WORLD");
+
+        let result = extract_etf_name("global-equity-income-etf-holdings.csv").unwrap();
+        assert_eq!(result, "GLOBAL-EQUITY-INCOME");
+    }
+
+    #[test]
+    fn test_extract_etf_name_containing_etf() {
+        // Only the final suffix is stripped
+        let result = extract_etf_name("etf-holdings-etf-holdings.csv").unwrap();
+        assert_eq!(result, "ETF-HOLDINGS");
+
+        let result = extract_etf_name("netflix-etf-etf-holdings.csv").unwrap();
+        assert_eq!(result, "NETFLIX-ETF");
+    }
+
+    #[test]
+    fn test_extract_etf_name_case_insensitive_suffix() {
+        let result = extract_etf_name("Spy-ETF-Holdings.CSV").unwrap();
+        assert_eq!(result, "SPY");
+    }
+
+    #[test]
+    fn test_extract_etf_name_suffix_only_fallback() {
+        // Nothing left before the suffix, so fall back to the whole name
+        let result = extract_etf_name("-etf-holdings.csv").unwrap();
+        assert_eq!(result, "-ETF-HOLDINGS");
+
+        let result = extract_etf_name("holdings.csv").unwrap();
+        assert_eq!(result, "HOLDINGS");
+    }
+
     #[test]
     fn test_load_multiple_holdings_empty() {
         let paths: Vec<&str> = vec![];
         let result = load_multiple_holdings(paths);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_synthesized_symbols_with_float_number_column() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("spy-etf-holdings.csv");
+        std::fs::write(
+            &csv_path,
+            "No.,Symbol,Name,% Weight,Shares\n1.0,AAPL,Apple,5%,100\n2.0,,Cash,1%,0\n",
+        ).unwrap();
+
+        let df = load_holdings_csv(&csv_path).unwrap();
+        let symbols = df.column("Symbol").unwrap().str().unwrap();
+        assert_eq!(symbols.get(1), Some("SPY-2"));
+    }
+
+    #[test]
+    fn test_load_directory_upper_case_filename() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(
+            temp_dir.path().join("SPY-ETF-HOLDINGS.CSV"),
+            "No.,Symbol,Name,% Weight,Shares\n1,AAPL,Apple,5%,100\n",
+        ).unwrap();
+
+        let df = load_portfolio_from_directory(temp_dir.path()).unwrap();
+        assert_eq!(crate::analysis::get_etf_list(&df).unwrap(), vec!["SPY"]);
+    }
+
+    #[test]
+    fn test_synthesized_symbols_for_whitespace_only_symbol() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("aaa-etf-holdings.csv");
+        std::fs::write(
+            &csv_path,
+            "No.,Symbol,Name,% Weight,Shares\n1,AAPL,Apple,5%,100\n2,   ,Cash,1%,0\n3, n/a ,Other,1%,0\n",
+        ).unwrap();
+
+        let df = trim_string_columns(&load_holdings_csv(&csv_path).unwrap()).unwrap();
+        let symbols = df.column("Symbol").unwrap().str().unwrap();
+        assert_eq!(symbols.get(1), Some("AAA-2"));
+        assert_eq!(symbols.get(2), Some("AAA-3"));
+    }
+
+    #[test]
+    fn test_inspect_weight_column() {
+        let df = df! {
+            "Weight" => &[
+                Some("5.25%"), Some("0.5 %"), Some("$1,234.56"), Some("0.0525"),
+                Some("N/A"), Some(""), None
+            ]
+        }.unwrap();
+
+        let report = inspect_weight_column(&df, "Weight").unwrap();
+        assert_eq!(report, WeightColumnReport {
+            percent: 2,
+            currency: 1,
+            numeric: 1,
+            unparseable: 1,
+            null: 2,
+        });
+
+        // Numeric columns are all bare numbers
+        let numeric = df! { "Weight" => &[Some(0.5), None] }.unwrap();
+        let report = inspect_weight_column(&numeric, "Weight").unwrap();
+        assert_eq!((report.numeric, report.null), (1, 1));
+    }
+
+    #[test]
+    fn test_trim_string_columns() {
+        let df = df! {
+            "ETF" => &[" SPY", "SPY"],
+            "Symbol" => &["AAPL  ", "AAPL"],
+            "Name" => &["Apple ", "Apple"],
+            "Weight" => &[" 5%", "5%"]
+        }.unwrap();
+
+        let trimmed = trim_string_columns(&df).unwrap();
+        let symbols = trimmed.column("Symbol").unwrap().str().unwrap();
+        assert_eq!(symbols.get(0), Some("AAPL"));
+        assert_eq!(trimmed.column("ETF").unwrap().str().unwrap().get(0), Some("SPY"));
+        assert_eq!(trimmed.column("Name").unwrap().str().unwrap().get(0), Some("Apple"));
+
+        // Other columns are left untouched
+        assert_eq!(trimmed.column("Weight").unwrap().str().unwrap().get(0), Some(" 5%"));
+    }
+
+    #[test]
+    fn test_column_config_builder() {
+        let config = ColumnConfig::builder()
+            .symbol("Ticker")
+            .weight("% of Assets")
+            .build();
+
+        assert_eq!(config.symbol_col, "Ticker");
+        assert_eq!(config.weight_col, "% of Assets");
+
+        // Unspecified fields keep their defaults
+        let default = ColumnConfig::default();
+        assert_eq!(config.name_col, default.name_col);
+        assert_eq!(config.shares_col, default.shares_col);
+        assert_eq!(config.number_col, default.number_col);
+        assert_eq!(config.weight_whole_col, None);
+        assert_eq!(config.weight_frac_col, None);
+
+        assert_eq!(ColumnConfig::builder().build(), default);
+    }
+
+    #[test]
+    fn test_load_holdings_combines_split_weight_columns() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("test-etf-holdings.csv");
+        std::fs::write(
+            &csv_path,
+            "No.,Symbol,Name,Weight_Whole,Weight_Frac\n1,AAPL,Apple,5,25\n2,MSFT,Microsoft,3,5\n3,NVDA,Nvidia,3,05\n",
+        ).unwrap();
+
+        let config = ColumnConfig {
+            weight_whole_col: Some("Weight_Whole".to_string()),
+            weight_frac_col: Some("Weight_Frac".to_string()),
+            ..Default::default()
+        };
+
+        let df = load_holdings_csv_with_config(&csv_path, &config).unwrap();
+
+        // Source columns are replaced by a single Weight column
+        assert!(df.column("Weight_Whole").is_err());
+        assert!(df.column("Weight_Frac").is_err());
+
+        let weights = df.column("Weight").unwrap().str().unwrap();
+        assert_eq!(weights.get(0), Some("5.25%"));
+
+        let parsed = crate::analysis::weight_values(&df).unwrap();
+        assert!((parsed[0].unwrap() - 0.0525).abs() < 1e-12);
+        assert!((parsed[1].unwrap() - 0.035).abs() < 1e-12);
+
+        // A leading zero in the fraction is kept: 3 and 05 make 3.05%, not 3.5%
+        assert_eq!(weights.get(2), Some("3.05%"));
+        assert!((parsed[2].unwrap() - 0.0305).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_combine_weight_columns_rejects_numeric_fraction() {
+        let df = df! {
+            "Weight_Whole" => &[3i64],
+            "Weight_Frac" => &[5i64],
+        }.unwrap();
+
+        assert!(combine_weight_columns(df, "Weight_Whole", "Weight_Frac").is_err());
+    }
+
+    #[test]
+    fn test_add_weight_pct_column() {
+        let df = df! {
+            "ETF" => &["SPY", "SPY", "SPY"],
+            "Symbol" => &["AAPL", "MSFT", "CASH"],
+            "Weight" => &["5%", "0.125", "N/A"]
+        }.unwrap();
+
+        let result = add_weight_pct_column(&df).unwrap();
+        let pct = result.column("Weight_Pct").unwrap().str().unwrap();
+        assert_eq!(pct.get(0), Some("5.00%"));
+        assert_eq!(pct.get(1), Some("12.50%"));
+        assert_eq!(pct.get(2), None);
+    }
+
+    #[test]
+    fn test_is_holdings_file_in_dir() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let dir = temp_dir.path();
+
+        assert!(is_holdings_file_in_dir(dir.join("out-etf-holdings.csv"), dir));
+        assert!(is_holdings_file_in_dir(dir.join("OUT-ETF-HOLDINGS.CSV"), dir));
+        assert!(!is_holdings_file_in_dir(dir.join("out.csv"), dir));
+        assert!(!is_holdings_file_in_dir("out-etf-holdings.csv", dir.join("missing")));
+    }
+
+    #[test]
+    fn test_export_zstd_parquet_round_trip() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("data.parquet");
+        let df = df! {
+            "Symbol" => &["AAPL", "MSFT"],
+            "Weight" => &["5%", "4%"]
+        }.unwrap();
+
+        let options = ExportOptions {
+            force: true,
+            parquet_compression: ParquetCodec::Zstd,
+            ..Default::default()
+        };
+        assert!(export_dataframe_with_options(&df, &path, &options).unwrap());
+
+        let imported = import_dataframe(&path).unwrap();
+        assert!(imported.equals(&df));
+    }
+
+    #[test]
+    fn test_file_format_from_path_gz() {
+        assert_eq!(FileFormat::from_path("data.csv.gz"), FileFormat::CsvGz);
+        assert_eq!(FileFormat::from_path("DATA.CSV.GZ"), FileFormat::CsvGz);
+        assert_ne!(FileFormat::from_path("data.parquet.gz"), FileFormat::CsvGz);
+        assert_ne!(FileFormat::from_path("data.gz"), FileFormat::CsvGz);
+    }
+
+    #[test]
+    fn test_export_gzip_csv_round_trip() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("data.csv.gz");
+        let df = df! {
+            "Symbol" => &["AAPL", "MSFT"],
+            "Weight" => &["5%", "4%"]
+        }.unwrap();
+
+        assert!(export_dataframe(&df, &path, true).unwrap());
+
+        // The file is actually gzipped, not plain text
+        let bytes = std::fs::read(&path).unwrap();
+        assert_eq!(&bytes[..2], &[0x1f, 0x8b]);
+
+        let imported = import_dataframe(&path).unwrap();
+        assert!(imported.equals(&df));
+    }
+
+    #[test]
+    fn test_export_renames_columns() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("data.csv");
+        let df = df! {
+            "Symbol" => &["AAPL"],
+            "Weight" => &["5%"]
+        }.unwrap();
+
+        let rename = parse_column_renames(&["Symbol:Ticker".to_string(), "Weight:% of Assets".to_string()]).unwrap();
+        let options = ExportOptions {
+            rename,
+            ..Default::default()
+        };
+        assert!(export_dataframe_with_options(&df, &path, &options).unwrap());
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(content.lines().next().unwrap(), "Ticker,% of Assets");
+
+        let options = ExportOptions {
+            force: true,
+            rename: vec![("Ticker".to_string(), "Symbol".to_string())],
+            ..Default::default()
+        };
+        assert!(export_dataframe_with_options(&df, &path, &options).is_err());
+
+        assert!(parse_column_renames(&["Symbol".to_string()]).is_err());
+        assert!(parse_column_renames(&["Symbol:".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_export_renamed_list_column_to_csv() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("assets.csv");
+        let holdings = df! {
+            "ETF" => &["SPY", "QQQ", "SPY"],
+            "Symbol" => &["AAPL", "AAPL", "MSFT"],
+            "Name" => &["Apple", "Apple", "Microsoft"],
+        }.unwrap();
+        let df = crate::analysis::aggregate_assets(&holdings, crate::analysis::AssetsSortBy::Symbol).unwrap();
+
+        let options = ExportOptions {
+            rename: vec![("ETFs".to_string(), "Funds".to_string())],
+            ..Default::default()
+        };
+        assert!(export_dataframe_with_options(&df, &path, &options).unwrap());
+        let content = std::fs::read_to_string(&path).unwrap();
+        let mut lines = content.lines();
+        assert_eq!(lines.next().unwrap(), "Symbol,Name,ETF_Count,Funds");
+        assert_eq!(lines.next().unwrap(), "AAPL,Apple,2,\"QQQ, SPY\"");
+    }
+
+    #[test]
+    fn test_export_parquet_list_as_array() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("assets.parquet");
+        let holdings = df! {
+            "ETF" => &["SPY", "QQQ", "SPY"],
+            "Symbol" => &["AAPL", "AAPL", "MSFT"],
+            "Name" => &["Apple", "Apple", "Microsoft"],
+        }.unwrap();
+        let df = crate::analysis::aggregate_assets(&holdings, crate::analysis::AssetsSortBy::Symbol).unwrap();
+
+        let options = ExportOptions {
+            list_as_array: true,
+            ..Default::default()
+        };
+        assert!(export_dataframe_with_options(&df, &path, &options).unwrap());
+
+        let imported = import_dataframe(&path).unwrap();
+        let etfs = imported.column("ETFs").unwrap();
+        assert_eq!(etfs.dtype(), &DataType::List(Box::new(DataType::String)));
+        assert_eq!(etfs.list().unwrap().get_as_series(0).unwrap().len(), 2);
+
+        // Without list_as_array (and always in CSV) the lists are joined
+        let options = ExportOptions { force: true, ..Default::default() };
+        assert!(export_dataframe_with_options(&df, &path, &options).unwrap());
+        let imported = import_dataframe(&path).unwrap();
+        assert_eq!(imported.column("ETFs").unwrap().str().unwrap().get(0), Some("QQQ, SPY"));
+
+        let csv_path = temp_dir.path().join("assets.csv");
+        assert!(export_dataframe_with_options(&df, &csv_path, &options).unwrap());
+        let content = std::fs::read_to_string(&csv_path).unwrap();
+        assert!(content.contains("AAPL,Apple,2,\"QQQ, SPY\""), "{}", content);
+    }
+
+    #[test]
+    fn test_export_force_formats_only_covers_listed_formats() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("data.csv");
+        let df = df! {
+            "Symbol" => &["AAPL"],
+            "Weight" => &["5%"]
+        }.unwrap();
+        std::fs::write(&csv_path, "old").unwrap();
+
+        let options = ExportOptions {
+            force_formats: FileFormat::parse_names(&["csv".to_string()]).unwrap(),
+            ..Default::default()
+        };
+        assert!(export_dataframe_with_options(&df, &csv_path, &options).unwrap());
+        assert_ne!(std::fs::read_to_string(&csv_path).unwrap(), "old");
+
+        assert_eq!(FileFormat::try_from_name(".parquet"), Some(vec![FileFormat::Parquet]));
+        assert!(FileFormat::parse_names(&["xlsx".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_parquet_codec_try_from_str() {
+        assert_eq!(ParquetCodec::try_from_str("ZSTD"), Some(ParquetCodec::Zstd));
+        assert_eq!(ParquetCodec::try_from_str("none"), Some(ParquetCodec::None));
+        assert_eq!(ParquetCodec::try_from_str("brotli"), None);
+        assert_eq!(ParquetCodec::default(), ParquetCodec::Snappy);
+    }
+
+    #[test]
+    fn test_export_csv_null_value() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("out.csv");
+
+        let df = df! {
+            "Symbol" => &["AAPL", "MSFT"],
+            "Weight" => &[Some(0.05), None]
+        }.unwrap();
+
+        let options = ExportOptions {
+            force: true,
+            null_value: Some("NA".to_string()),
+            ..Default::default()
+        };
+        assert!(export_dataframe_with_options(&df, &csv_path, &options).unwrap());
+
+        let content = std::fs::read_to_string(&csv_path).unwrap();
+        assert!(content.contains("MSFT,NA"));
+    }
+
+    #[test]
+    fn test_load_holdings_files_missing() {
+        let result = load_holdings_files_with_config(
+            vec!["nonexistent-etf-holdings.csv"],
+            &ColumnConfig::default(),
+        );
+        assert!(result.is_err());
+        assert!(result.unwrap_err().to_string().contains("nonexistent-etf-holdings.csv"));
+    }
+
+    #[test]
+    fn test_read_file_list() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let list_path = temp_dir.path().join("files.txt");
+        std::fs::write(&list_path, "# my picks\na.csv\n\n  b.csv  \n").unwrap();
+
+        let paths = read_file_list(&list_path).unwrap();
+        assert_eq!(paths, vec!["a.csv", "b.csv"]);
+    }
 }
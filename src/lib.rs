@@ -1,11 +1,15 @@
 pub mod cli;
 pub mod config;
+pub mod context;
 pub mod error;
+pub mod fixtures;
 pub mod io;
+pub mod locale;
 pub mod analysis;
 pub mod portfolio;
 pub mod report;
 
+pub use context::AnalyzerContext;
 pub use error::{Error, Result};
 
 /// Main library functionality
@@ -0,0 +1,132 @@
+use crate::Result;
+
+/// Separators for numbers shown to people (summaries and printed tables)
+/// Data files are always written with plain machine-standard numbers
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NumberFormat {
+    /// Digit-grouping character, or None for no grouping
+    pub thousands: Option<char>,
+    /// Decimal separator
+    pub decimal: char,
+}
+
+impl Default for NumberFormat {
+    /// The C locale: no grouping and a '.' decimal point
+    fn default() -> Self {
+        NumberFormat {
+            thousands: None,
+            decimal: '.',
+        }
+    }
+}
+
+impl NumberFormat {
+    /// Separators for a locale such as "de", "de-DE", "fr_FR.UTF-8", "en-US" or "C"
+    /// Only the language (plus a few regions that differ) is considered; returns None for unknown locales
+    pub fn try_from_locale(locale: &str) -> Option<Self> {
+        let locale = locale.trim().to_lowercase().replace('_', "-");
+        let locale = locale.split('.').next().unwrap_or("");
+        let language = locale.split('-').next().unwrap_or("");
+
+        let (thousands, decimal) = match (language, locale) {
+            ("c" | "posix", _) => (None, '.'),
+            (_, "de-ch" | "it-ch") => (Some('\''), '.'),
+            ("en" | "ja" | "zh" | "ko" | "he" | "th", _) => (Some(','), '.'),
+            ("de" | "es" | "it" | "nl" | "pt" | "da" | "id" | "tr" | "el", _) => (Some('.'), ','),
+            ("fr" | "ru" | "pl" | "sv" | "cs" | "nb" | "no" | "fi" | "uk" | "sk" | "hu", _) => (Some(' '), ','),
+            _ => return None,
+        };
+
+        Some(NumberFormat { thousands, decimal })
+    }
+
+    /// Parse a --locale value, erroring on locales without known separators
+    pub fn from_locale(locale: &str) -> Result<Self> {
+        Self::try_from_locale(locale).ok_or_else(|| crate::Error::Parse(
+            format!("Unknown locale '{}' (e.g. C, en-US, de-DE, fr-FR)", locale)
+        ))
+    }
+
+    /// Format a count with digit grouping, e.g. 1234 -> "1.234" in German
+    pub fn count(&self, n: u64) -> String {
+        self.group(&n.to_string())
+    }
+
+    /// Format a number with a fixed number of decimals, e.g. 1234.5 -> "1.234,50" in German
+    pub fn number(&self, value: f64, decimals: usize) -> String {
+        let formatted = format!("{:.*}", decimals, value.abs());
+        let (whole, frac) = match formatted.split_once('.') {
+            Some((whole, frac)) => (whole, Some(frac)),
+            None => (formatted.as_str(), None),
+        };
+
+        let mut result = String::new();
+        if value.is_sign_negative() && formatted.chars().any(|c| c.is_ascii_digit() && c != '0') {
+            result.push('-');
+        }
+        result.push_str(&self.group(whole));
+        if let Some(frac) = frac {
+            result.push(self.decimal);
+            result.push_str(frac);
+        }
+        result
+    }
+
+    /// Format a fraction as a percentage, e.g. 0.125 -> "12,50%" in German
+    pub fn percent(&self, fraction: f64, decimals: usize) -> String {
+        format!("{}%", self.number(fraction * 100.0, decimals))
+    }
+
+    /// Use these separators when printing DataFrames as tables
+    pub fn apply_to_tables(&self) {
+        polars_core::fmt::set_thousands_separator(self.thousands);
+        polars_core::fmt::set_decimal_separator(Some(self.decimal));
+    }
+
+    /// Insert the thousands separator into a string of ASCII digits
+    fn group(&self, digits: &str) -> String {
+        let Some(separator) = self.thousands else {
+            return digits.to_string();
+        };
+
+        let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+        for (i, c) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                grouped.push(separator);
+            }
+            grouped.push(c);
+        }
+        grouped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_is_plain() {
+        let format = NumberFormat::default();
+        assert_eq!(format.count(1_234_567), "1234567");
+        assert_eq!(format.percent(0.125, 2), "12.50%");
+        assert_eq!(NumberFormat::try_from_locale("C"), Some(format));
+    }
+
+    #[test]
+    fn test_locale_separators() {
+        let de = NumberFormat::from_locale("de-DE").unwrap();
+        assert_eq!(de.count(1_234_567), "1.234.567");
+        assert_eq!(de.number(1234.56, 2), "1.234,56");
+        assert_eq!(de.number(-0.001, 2), "0,00");
+        assert_eq!(de.percent(0.125, 2), "12,50%");
+
+        let en = NumberFormat::from_locale("en_US.UTF-8").unwrap();
+        assert_eq!(en.number(-1234.5, 1), "-1,234.5");
+        assert_eq!(en.count(999), "999");
+
+        let fr = NumberFormat::from_locale("fr").unwrap();
+        assert_eq!(fr.number(1234.5, 2), "1 234,50");
+
+        assert!(NumberFormat::from_locale("xx").is_err());
+    }
+}
@@ -1,12 +1,18 @@
 use polars::prelude::*;
 use rayon::prelude::*;
+use std::collections::HashMap;
 use crate::Result;
 
+/// Default tolerance when checking that portfolio weights sum to 1.0
+pub const DEFAULT_WEIGHT_TOLERANCE: f64 = 1e-6;
+
 /// Portfolio configuration and state
 pub struct Portfolio {
     pub etfs: Vec<String>,
     pub weights: Vec<f64>,
     pub data: Option<DataFrame>,
+    /// Original weight sum when with_weights_tolerance rescaled the weights to 1.0, for the caller to report
+    pub normalized_from: Option<f64>,
 }
 
 impl Portfolio {
@@ -20,11 +26,29 @@ impl Portfolio {
             etfs,
             weights,
             data: None,
+            normalized_from: None,
         }
     }
 
     /// Create a portfolio with custom weights
+    /// Weights must sum to 1.0 within DEFAULT_WEIGHT_TOLERANCE
     pub fn with_weights(etfs: Vec<String>, weights: Vec<f64>) -> Result<Self> {
+        Self::with_weights_tolerance(etfs, weights, DEFAULT_WEIGHT_TOLERANCE, None)
+    }
+
+    /// Create a portfolio with custom weights and a configurable sum tolerance
+    /// Weights summing to 1.0 within `tolerance` are accepted as-is.
+    /// If `normalize_within` is set and the sum is off by no more than that band,
+    /// the weights are rescaled to sum to 1.0 and the original sum is kept in `normalized_from`.
+    /// Anything further from 1.0 is rejected.
+    /// The library never prints: callers building portfolios from user-entered weights must check
+    /// `normalized_from` and warn the user that their weights were rescaled.
+    pub fn with_weights_tolerance(
+        etfs: Vec<String>,
+        weights: Vec<f64>,
+        tolerance: f64,
+        normalize_within: Option<f64>,
+    ) -> Result<Self> {
         if etfs.len() != weights.len() {
             return Err(crate::Error::Other(
                 "Number of ETFs must match number of weights".to_string()
@@ -32,16 +56,23 @@ impl Portfolio {
         }
 
         let sum: f64 = weights.iter().sum();
-        if (sum - 1.0).abs() > 1e-6 {
+        let deviation = (sum - 1.0).abs();
+
+        let (weights, normalized_from) = if deviation <= tolerance {
+            (weights, None)
+        } else if normalize_within.is_some_and(|band| deviation <= band) && sum > 0.0 {
+            (weights.iter().map(|w| w / sum).collect(), Some(sum))
+        } else {
             return Err(crate::Error::Other(
                 format!("Weights must sum to 1.0, got {}", sum)
             ));
-        }
+        };
 
         Ok(Portfolio {
             etfs,
             weights,
             data: None,
+            normalized_from,
         })
     }
 
@@ -68,6 +99,35 @@ impl Portfolio {
         Ok(portfolio_return)
     }
 
+    /// Calculate portfolio return from per-ETF returns keyed by ETF symbol (case-insensitive)
+    /// Errors if any portfolio ETF has no return; extra entries in `returns` are ignored
+    pub fn calculate_portfolio_return_from_map(&self, returns: &HashMap<String, f64>) -> Result<f64> {
+        let returns_upper: HashMap<String, f64> = returns
+            .iter()
+            .map(|(etf, r)| (etf.to_uppercase(), *r))
+            .collect();
+
+        let missing: Vec<&str> = self
+            .etfs
+            .iter()
+            .filter(|etf| !returns_upper.contains_key(&etf.to_uppercase()))
+            .map(String::as_str)
+            .collect();
+        if !missing.is_empty() {
+            return Err(crate::Error::Other(
+                format!("No return given for ETF(s): {}", missing.join(", "))
+            ));
+        }
+
+        let ordered: Vec<f64> = self
+            .etfs
+            .iter()
+            .map(|etf| returns_upper[&etf.to_uppercase()])
+            .collect();
+
+        self.calculate_portfolio_return(&ordered)
+    }
+
     /// Rebalance portfolio to equal weights
     pub fn rebalance_equal(&mut self) {
         let n = self.etfs.len();
@@ -75,6 +135,65 @@ impl Portfolio {
         self.weights = vec![weight; n];
     }
 
+    /// Suggest buy/sell adjustments to move from the current weights to target weights
+    /// Returns a DataFrame with columns: ETF, Current_Weight, Target_Weight, Delta, Action
+    /// Delta is Target_Weight - Current_Weight; Action is "Buy", "Sell" or "Hold".
+    /// ETFs missing from `targets` get a target of 0.0; target ETFs not held get a current weight of 0.0.
+    pub fn rebalance_plan(&self, targets: &HashMap<String, f64>) -> Result<DataFrame> {
+        let target_sum: f64 = targets.values().sum();
+        if (target_sum - 1.0).abs() > DEFAULT_WEIGHT_TOLERANCE {
+            return Err(crate::Error::Other(
+                format!("Target weights must sum to 1.0, got {}", target_sum)
+            ));
+        }
+
+        // Match ETF symbols case-insensitively
+        let targets_upper: HashMap<String, f64> = targets
+            .iter()
+            .map(|(etf, w)| (etf.to_uppercase(), *w))
+            .collect();
+
+        let mut etfs: Vec<String> = self.etfs.clone();
+        let mut extra_targets: Vec<String> = targets_upper
+            .keys()
+            .filter(|etf| !self.etfs.iter().any(|e| e.to_uppercase() == **etf))
+            .cloned()
+            .collect();
+        extra_targets.sort();
+        etfs.extend(extra_targets);
+
+        let current: Vec<f64> = (0..etfs.len())
+            .map(|i| self.weights.get(i).copied().unwrap_or(0.0))
+            .collect();
+        let target: Vec<f64> = etfs
+            .iter()
+            .map(|etf| targets_upper.get(&etf.to_uppercase()).copied().unwrap_or(0.0))
+            .collect();
+        let delta: Vec<f64> = target.iter().zip(current.iter()).map(|(t, c)| t - c).collect();
+        let action: Vec<&str> = delta
+            .iter()
+            .map(|d| {
+                if *d > DEFAULT_WEIGHT_TOLERANCE {
+                    "Buy"
+                } else if *d < -DEFAULT_WEIGHT_TOLERANCE {
+                    "Sell"
+                } else {
+                    "Hold"
+                }
+            })
+            .collect();
+
+        let plan = df! {
+            "ETF" => etfs,
+            "Current_Weight" => current,
+            "Target_Weight" => target,
+            "Delta" => delta,
+            "Action" => action
+        }?;
+
+        Ok(plan)
+    }
+
     /// Get portfolio summary
     pub fn summary(&self) -> String {
         let mut summary = String::from("Portfolio Summary:\n");
@@ -86,6 +205,32 @@ impl Portfolio {
 
         summary
     }
+
+    /// Serialize the portfolio state as JSON: ETFs, weights and the shape of the loaded data
+    /// The data itself is not included; rows/columns are null when no data is loaded
+    pub fn to_json(&self) -> Result<String> {
+        let etfs = self.etfs
+            .iter()
+            .map(|etf| crate::report::json_string(etf))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let weights = self.weights
+            .iter()
+            .map(|w| w.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let (rows, columns) = match &self.data {
+            Some(df) => (df.height().to_string(), df.width().to_string()),
+            None => ("null".to_string(), "null".to_string()),
+        };
+
+        Ok(format!(
+            "{{\"etfs\": [{}], \"weights\": [{}], \"rows\": {}, \"columns\": {}}}",
+            etfs, weights, rows, columns
+        ))
+    }
 }
 
 /// Calculate correlation matrix for multiple return series
@@ -159,6 +304,30 @@ fn pearson_correlation(x: &Float64Chunked, y: &Float64Chunked) -> Result<f64> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_portfolio_to_json() {
+        let mut portfolio = Portfolio::new(vec!["SPY".to_string(), "QQQ".to_string()]);
+        let df = df! {
+            "ETF" => &["SPY", "QQQ", "QQQ"],
+            "Symbol" => &["AAPL", "AAPL", "NVDA"]
+        }.unwrap();
+        portfolio.load_data(df).unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(&portfolio.to_json().unwrap()).unwrap();
+        assert_eq!(json["etfs"], serde_json::json!(["SPY", "QQQ"]));
+        assert_eq!(json["rows"], 3);
+        assert_eq!(json["columns"], 2);
+
+        let total: f64 = json["weights"].as_array().unwrap().iter().map(|w| w.as_f64().unwrap()).sum();
+        assert!((total - 1.0).abs() < 1e-9);
+
+        // Names are escaped and missing data is null
+        let odd = Portfolio::new(vec!["A\"B".to_string()]);
+        let json: serde_json::Value = serde_json::from_str(&odd.to_json().unwrap()).unwrap();
+        assert_eq!(json["etfs"][0], "A\"B");
+        assert!(json["rows"].is_null());
+    }
+
     #[test]
     fn test_portfolio_new_equal_weights() {
         let portfolio = Portfolio::new(vec!["SPY".to_string(), "QQQ".to_string()]);
@@ -174,6 +343,87 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_portfolio_weights_within_tolerance() {
+        // 49.9% + 50.0% = 99.9%, accepted with a 0.5% tolerance
+        let portfolio = Portfolio::with_weights_tolerance(
+            vec!["SPY".to_string(), "QQQ".to_string()],
+            vec![0.499, 0.5],
+            0.005,
+            None,
+        ).unwrap();
+        assert_eq!(portfolio.weights, vec![0.499, 0.5]);
+        assert_eq!(portfolio.normalized_from, None);
+    }
+
+    #[test]
+    fn test_portfolio_weights_outside_tolerance() {
+        let result = Portfolio::with_weights_tolerance(
+            vec!["SPY".to_string(), "QQQ".to_string()],
+            vec![0.45, 0.5],
+            0.005,
+            Some(0.01),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_portfolio_weights_normalized_within_band() {
+        let portfolio = Portfolio::with_weights_tolerance(
+            vec!["SPY".to_string(), "QQQ".to_string()],
+            vec![0.499, 0.5],
+            1e-6,
+            Some(0.01),
+        ).unwrap();
+        let sum: f64 = portfolio.weights.iter().sum();
+        assert!((sum - 1.0).abs() < 1e-12);
+        assert!((portfolio.weights[0] - 0.499 / 0.999).abs() < 1e-12);
+        assert!((portfolio.normalized_from.unwrap() - 0.999).abs() < 1e-12);
+
+        // Weights already within tolerance are not rescaled, so there is nothing to report
+        let portfolio = Portfolio::with_weights_tolerance(
+            vec!["SPY".to_string(), "QQQ".to_string()],
+            vec![0.5, 0.5],
+            1e-6,
+            Some(0.01),
+        ).unwrap();
+        assert_eq!(portfolio.normalized_from, None);
+    }
+
+    #[test]
+    fn test_rebalance_plan_overweight_sells() {
+        let portfolio = Portfolio::with_weights(
+            vec!["SPY".to_string(), "QQQ".to_string()],
+            vec![0.7, 0.3],
+        ).unwrap();
+
+        let targets: HashMap<String, f64> = [
+            ("SPY".to_string(), 0.5),
+            ("QQQ".to_string(), 0.5),
+        ].into_iter().collect();
+
+        let plan = portfolio.rebalance_plan(&targets).unwrap();
+        assert_eq!(
+            plan.get_column_names(),
+            vec!["ETF", "Current_Weight", "Target_Weight", "Delta", "Action"]
+        );
+
+        let actions: Vec<&str> = plan.column("Action").unwrap().str().unwrap()
+            .into_iter().flatten().collect();
+        assert_eq!(actions, vec!["Sell", "Buy"]);
+
+        let deltas = plan.column("Delta").unwrap().f64().unwrap();
+        assert!((deltas.get(0).unwrap() + 0.2).abs() < 1e-9);
+        assert!((deltas.get(1).unwrap() - 0.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_rebalance_plan_targets_must_sum_to_one() {
+        let portfolio = Portfolio::new(vec!["SPY".to_string()]);
+        let targets: HashMap<String, f64> = [("SPY".to_string(), 0.8)].into_iter().collect();
+        assert!(portfolio.rebalance_plan(&targets).is_err());
+    }
+
     #[test]
     fn test_portfolio_return_calculation() {
         let portfolio = Portfolio::new(vec!["SPY".to_string(), "QQQ".to_string()]);
@@ -182,6 +432,23 @@ mod tests {
         assert!((portfolio_return - 0.15).abs() < 1e-6);
     }
 
+    #[test]
+    fn test_portfolio_return_from_map() {
+        let portfolio = Portfolio::with_weights(
+            vec!["SPY".to_string(), "QQQ".to_string()],
+            vec![0.75, 0.25],
+        ).unwrap();
+
+        // Map order and case don't matter
+        let returns = HashMap::from([("qqq".to_string(), 0.20), ("SPY".to_string(), 0.08)]);
+        let portfolio_return = portfolio.calculate_portfolio_return_from_map(&returns).unwrap();
+        assert!((portfolio_return - 0.11).abs() < 1e-9);
+
+        let partial = HashMap::from([("SPY".to_string(), 0.08)]);
+        let err = portfolio.calculate_portfolio_return_from_map(&partial).unwrap_err();
+        assert!(err.to_string().contains("QQQ"));
+    }
+
     #[test]
     fn test_correlation_matrix_parallel() {
         // Create a simple DataFrame with 3 columns for testing
@@ -1,6 +1,8 @@
 use polars::prelude::*;
 use std::fmt::Write as FmtWrite;
 use crate::Result;
+use crate::analysis::DataWarning;
+use crate::io::ExportOptions;
 use crate::portfolio::Portfolio;
 
 /// Generate a text report from analysis results
@@ -52,6 +54,98 @@ pub fn generate_statistics_csv(df: &DataFrame, output_path: &str) -> Result<()>
     Ok(())
 }
 
+/// Write the asset distribution by ETF count as CSV with columns: ETF_Count, Asset_Count
+/// Takes the output of aggregate_assets; rows match the text from analysis::summarize_assets
+/// Writes through export_dataframe_with_options, so it returns false if the user declined to overwrite
+pub fn asset_distribution_csv(assets_df: &DataFrame, output_path: &str, options: &ExportOptions) -> Result<bool> {
+    let distribution = crate::analysis::etf_count_distribution(assets_df)?;
+    crate::io::export_dataframe_with_options(&distribution, output_path, options)
+}
+
+/// Quote a string for JSON output
+pub(crate) fn json_string(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Data-quality warnings as a JSON array of {kind, message, etf} objects (etf may be null)
+pub fn warnings_json(warnings: &[DataWarning]) -> String {
+    let entries: Vec<String> = warnings
+        .iter()
+        .map(|w| {
+            format!(
+                "{{\"kind\": \"{}\", \"message\": {}, \"etf\": {}}}",
+                w.kind.as_str(),
+                json_string(&w.message),
+                w.etf.as_deref().map(json_string).unwrap_or_else(|| "null".to_string())
+            )
+        })
+        .collect();
+
+    format!("[{}]", entries.join(", "))
+}
+
+/// Summary statistics for the assets function as a JSON object
+/// Takes the output of aggregate_assets: total_assets plus the distribution by ETF count,
+/// and any data-quality warnings for the underlying holdings
+pub fn assets_summary_json(assets_df: &DataFrame, warnings: &[DataWarning]) -> Result<String> {
+    let distribution = crate::analysis::etf_count_distribution(assets_df)?;
+    let etf_counts = distribution.column("ETF_Count")?.u32()?;
+    let asset_counts = distribution.column("Asset_Count")?.u32()?;
+
+    let entries: Vec<String> = etf_counts
+        .into_iter()
+        .zip(asset_counts)
+        .filter_map(|(etf_count, asset_count)| {
+            Some(format!(
+                "{{\"etf_count\": {}, \"asset_count\": {}}}",
+                etf_count?,
+                asset_count?
+            ))
+        })
+        .collect();
+
+    Ok(format!(
+        "{{\"total_assets\": {}, \"distribution\": [{}], \"warnings\": {}}}",
+        assets_df.height(),
+        entries.join(", "),
+        warnings_json(warnings)
+    ))
+}
+
+/// Summary statistics for the summary function as a JSON object
+/// Takes the output of get_etf_summary; mirrors analysis::summarize_etfs, plus data-quality warnings
+pub fn etf_summary_json(summary_df: &DataFrame, warnings: &[DataWarning]) -> Result<String> {
+    let counts: Vec<u32> = summary_df.column("Asset_Count")?.u32()?.into_iter().flatten().collect();
+
+    Ok(format!(
+        "{{\"total_etfs\": {}, \"max_assets\": {}, \"min_assets\": {}, \"warnings\": {}}}",
+        summary_df.height(),
+        counts.iter().max().copied().unwrap_or(0),
+        counts.iter().min().copied().unwrap_or(0),
+        warnings_json(warnings)
+    ))
+}
+
+/// Write a JSON summary next to an output file as `<output>.summary.json`
+/// An existing sidecar gets the usual overwrite prompt (see io::write_text_with_options)
+/// Returns the path written, or None if the user declined to overwrite
+pub fn write_summary_json(output_path: &str, json: &str, options: &ExportOptions) -> Result<Option<String>> {
+    let sidecar_path = format!("{}.summary.json", output_path);
+    let written = crate::io::write_text_with_options(&sidecar_path, &format!("{}\n", json), options)?;
+    Ok(written.then_some(sidecar_path))
+}
+
 /// Analysis metrics structure
 #[derive(Debug, Clone)]
 pub struct AnalysisMetrics {
@@ -122,4 +216,97 @@ mod tests {
         assert!(output.contains("QQQ"));
         assert!(output.contains("1.0000"));
     }
+
+    // Snapshot tests pin the exact text layout of the formatters; review changes with `cargo insta review`
+
+    #[test]
+    fn snapshot_correlation_matrix_3x3() {
+        let matrix = vec![
+            vec![1.0, 0.8, -0.25],
+            vec![0.8, 1.0, 0.1],
+            vec![-0.25, 0.1, 1.0],
+        ];
+        let labels = vec!["SPY".to_string(), "QQQ".to_string(), "TLT".to_string()];
+        insta::assert_snapshot!(format_correlation_matrix(&matrix, &labels));
+    }
+
+    #[test]
+    fn snapshot_two_etf_portfolio_report() {
+        let portfolio = Portfolio::with_weights(
+            vec!["SPY".to_string(), "QQQ".to_string()],
+            vec![0.6, 0.4],
+        ).unwrap();
+        let metrics = AnalysisMetrics::new(0.1523, 1.2345, 0.1875);
+        insta::assert_snapshot!(generate_text_report(&portfolio, &metrics));
+    }
+
+    #[test]
+    fn snapshot_etf_summary() {
+        let holdings = df! {
+            "ETF" => &["SPY", "SPY", "SPY", "QQQ"],
+            "Symbol" => &["AAPL", "MSFT", "NVDA", "AAPL"],
+            "Name" => &["Apple", "Microsoft", "Nvidia", "Apple"]
+        }.unwrap();
+        let summary_df = crate::analysis::get_etf_summary(&holdings).unwrap();
+        insta::assert_snapshot!(crate::analysis::summarize_etfs(&summary_df).unwrap());
+    }
+
+    #[test]
+    fn test_assets_summary_json() {
+        let df = df! {
+            "Symbol" => &["AAPL", "MSFT", "GOOGL"],
+            "Name" => &["Apple", "Microsoft", "Google"],
+            "ETF_Count" => &[2u32, 1u32, 1u32],
+            "ETFs" => &["SPY, QQQ", "SPY", "IWF"]
+        }.unwrap();
+
+        let json = assets_summary_json(&df, &[]).unwrap();
+        assert_eq!(
+            json,
+            "{\"total_assets\": 3, \"distribution\": [{\"etf_count\": 2, \"asset_count\": 1}, {\"etf_count\": 1, \"asset_count\": 2}], \"warnings\": []}"
+        );
+    }
+
+    #[test]
+    fn test_summary_json_includes_coverage_warning() {
+        let holdings = df! {
+            "ETF" => &["SPY", "SPY", "QQQ"],
+            "Symbol" => &["AAPL", "MSFT", "AAPL"],
+            "Name" => &["Apple", "Microsoft", "Apple"],
+            "Weight" => &["30%", "20%", "100%"]
+        }.unwrap();
+        let warnings = crate::analysis::data_quality_warnings(&holdings).unwrap();
+
+        let summary_df = crate::analysis::get_etf_summary(&holdings).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&etf_summary_json(&summary_df, &warnings).unwrap()).unwrap();
+
+        let entries = json["warnings"].as_array().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0]["kind"], "coverage");
+        assert_eq!(entries[0]["etf"], "SPY");
+        assert!(entries[0]["message"].as_str().unwrap().contains("50.00%"));
+    }
+
+    #[test]
+    fn test_asset_distribution_csv_matches_summary() {
+        let df = df! {
+            "Symbol" => &["AAPL", "MSFT", "GOOGL"],
+            "Name" => &["Apple", "Microsoft", "Google"],
+            "ETF_Count" => &[2u32, 1u32, 1u32],
+            "ETFs" => &["SPY, QQQ", "SPY", "IWF"]
+        }.unwrap();
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let csv_path = temp_dir.path().join("dist.csv");
+        let written = asset_distribution_csv(&df, csv_path.to_str().unwrap(), &ExportOptions::default()).unwrap();
+        assert!(written);
+
+        let content = std::fs::read_to_string(&csv_path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines, vec!["ETF_Count,Asset_Count", "2,1", "1,2"]);
+
+        let summary = crate::analysis::summarize_assets(&df).unwrap();
+        assert!(summary.contains("1 asset found in 2 ETFs"));
+        assert!(summary.contains("2 assets found in 1 ETF"));
+    }
 }
@@ -13,6 +13,155 @@ fn test_cli_runs() {
         .stderr(predicate::str::contains("Either --data-dir (-d) or --import (-i) must be specified"));
 }
 
+#[test]
+fn test_default_function_is_summary() {
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Total ETFs: 6"))
+        .stdout(predicate::str::contains("Portfolio").not());
+}
+
+#[test]
+fn test_unknown_function_fails() {
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("-f")
+        .arg("sumary")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown function 'sumary'"));
+}
+
+#[test]
+fn test_summary_locale_groups_thousands() {
+    let temp_dir = TempDir::new().unwrap();
+    let mut content = String::from("No.,Symbol,Name,% Weight,Shares\n");
+    for i in 1..=1234 {
+        content.push_str(&format!("{},SYM{},Holding {},0.05%,10\n", i, i, i));
+    }
+    fs::write(temp_dir.path().join("BIG-etf-holdings.csv"), content).unwrap();
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg(temp_dir.path())
+        .arg("-f")
+        .arg("summary")
+        .arg("--locale")
+        .arg("de")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Largest ETF contains 1.234 assets"));
+
+    // Without --locale counts stay ungrouped
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg(temp_dir.path())
+        .arg("-f")
+        .arg("summary")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Largest ETF contains 1234 assets"));
+}
+
+#[test]
+fn test_weight_sum_locale_decimal_separator() {
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("--etfs")
+        .arg("IVW")
+        .arg("-f")
+        .arg("weight-sum")
+        .arg("--locale")
+        .arg("de")
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"IVW: \d+,\d{2}%").unwrap());
+}
+
+#[test]
+fn test_portfolio_state_prompts_before_overwrite() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("state.txt");
+    fs::write(&output_path, "keep me\n").unwrap();
+
+    // Declining the prompt leaves the existing file alone
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("-f")
+        .arg("portfolio-state")
+        .arg("-o")
+        .arg(&output_path)
+        .write_stdin("n\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("already exists"));
+    assert_eq!(fs::read_to_string(&output_path).unwrap(), "keep me\n");
+
+    // --force overwrites without asking
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("-f")
+        .arg("portfolio-state")
+        .arg("-o")
+        .arg(&output_path)
+        .arg("--force")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Portfolio state saved to:"));
+    assert_ne!(fs::read_to_string(&output_path).unwrap(), "keep me\n");
+}
+
+#[test]
+fn test_compare_expands_etf_patterns() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("compare.csv");
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("--etfs")
+        .arg("IV*,IWF")
+        .arg("-f")
+        .arg("compare")
+        .arg("-o")
+        .arg(&output_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Comparing 2 ETFs"));
+
+    // The pattern becomes the matched ETF's column, not a literal "IV*" column
+    let content = fs::read_to_string(&output_path).unwrap();
+    let header = content.lines().next().unwrap();
+    assert!(header.starts_with("Symbol,IVW,IWF"), "header: {}", header);
+    assert!(!header.contains('*'));
+}
+
+#[test]
+fn test_gzip_rejects_non_csv_output() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("holdings.parquet");
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("-f")
+        .arg("export")
+        .arg("--gzip")
+        .arg("-o")
+        .arg(&output_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--gzip only applies to CSV output"));
+    assert!(!output_path.exists());
+}
+
 #[test]
 fn test_help_flag() {
     let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
@@ -69,234 +218,1014 @@ fn test_assets_function_with_output() {
 }
 
 #[test]
-fn test_assets_function_default_extension() {
+fn test_assets_function_rename_out() {
     let temp_dir = TempDir::new().unwrap();
-    let output_path = temp_dir.path().join("test_assets");
+    let output_path = temp_dir.path().join("assets.csv");
 
     let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
     cmd.arg("-d")
         .arg("./example-data")
         .arg("-f")
         .arg("assets")
+        .arg("--rename-out")
+        .arg("Symbol:Ticker")
         .arg("-o")
         .arg(&output_path)
         .assert()
         .success();
 
-    // Verify the file was created with .csv extension
-    let csv_path = temp_dir.path().join("test_assets.csv");
-    assert!(csv_path.exists());
-}
+    let content = fs::read_to_string(&output_path).unwrap();
+    assert!(content.lines().next().unwrap().starts_with("Ticker,Name,"));
 
-#[test]
-fn test_unique_function() {
     let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
     cmd.arg("-d")
         .arg("./example-data")
         .arg("-f")
-        .arg("unique")
+        .arg("assets")
+        .arg("--rename-out")
+        .arg("Ticker:Symbol")
+        .arg("-o")
+        .arg(temp_dir.path().join("bad.csv"))
         .assert()
-        .success()
-        .stdout(predicate::str::contains("unique assets"));
+        .failure();
 }
 
 #[test]
-fn test_unique_function_with_output() {
+fn test_assets_parquet_list_as_array() {
     let temp_dir = TempDir::new().unwrap();
-    let output_path = temp_dir.path().join("test_unique.csv");
+    let output_path = temp_dir.path().join("assets.parquet");
 
     let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
     cmd.arg("-d")
         .arg("./example-data")
         .arg("-f")
-        .arg("unique")
+        .arg("assets")
+        .arg("--list-as-array")
         .arg("-o")
         .arg(&output_path)
         .assert()
-        .success()
-        .stdout(predicate::str::contains("Unique assets saved to:"));
-
-    // Verify the file was created
-    assert!(output_path.exists());
+        .success();
 
-    // Verify column order: Symbol, Name, Weight, ETF
-    let content = fs::read_to_string(&output_path).unwrap();
-    let first_line = content.lines().next().unwrap();
-    assert_eq!(first_line, "Symbol,Name,Weight,ETF");
+    let df = etf_analyzer::io::import_dataframe(&output_path).unwrap();
+    let etfs = df.column("ETFs").unwrap();
+    assert_eq!(
+        etfs.dtype(),
+        &polars::prelude::DataType::List(Box::new(polars::prelude::DataType::String))
+    );
 }
 
 #[test]
-fn test_export_function_csv() {
+fn test_assets_function_default_extension() {
     let temp_dir = TempDir::new().unwrap();
-    let output_path = temp_dir.path().join("test_export.csv");
+    let output_path = temp_dir.path().join("test_assets");
 
     let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
     cmd.arg("-d")
         .arg("./example-data")
         .arg("-f")
-        .arg("export")
+        .arg("assets")
         .arg("-o")
         .arg(&output_path)
         .assert()
-        .success()
-        .stdout(predicate::str::contains("Successfully exported to:"));
-
-    // Verify the file was created
-    assert!(output_path.exists());
+        .success();
 
-    // Verify it's a valid CSV with expected columns
-    let content = fs::read_to_string(&output_path).unwrap();
-    assert!(content.contains("ETF,Symbol,Name,Weight"));
+    // Verify the file was created with .csv extension
+    let csv_path = temp_dir.path().join("test_assets.csv");
+    assert!(csv_path.exists());
 }
 
 #[test]
-fn test_export_function_parquet() {
+fn test_assets_function_weight_agg() {
     let temp_dir = TempDir::new().unwrap();
-    let output_path = temp_dir.path().join("test_export.parquet");
+    let output_path = temp_dir.path().join("test_assets.csv");
 
     let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
     cmd.arg("-d")
         .arg("./example-data")
         .arg("-f")
-        .arg("export")
+        .arg("assets")
+        .arg("--asset-weight-agg")
+        .arg("sum")
         .arg("-o")
         .arg(&output_path)
         .assert()
         .success();
 
-    // Verify the file was created
-    assert!(output_path.exists());
+    let content = fs::read_to_string(&output_path).unwrap();
+    assert!(content.contains("Symbol,Name,ETF_Count,ETFs,Weight_Sum"));
 }
 
 #[test]
-fn test_import_function() {
+fn test_assets_function_dist_output() {
     let temp_dir = TempDir::new().unwrap();
-    let export_path = temp_dir.path().join("test_export.parquet");
+    let dist_path = temp_dir.path().join("dist.csv");
 
-    // First export data
-    let mut export_cmd = Command::cargo_bin("etf_analyzer").unwrap();
-    export_cmd
-        .arg("-d")
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
         .arg("./example-data")
         .arg("-f")
-        .arg("export")
-        .arg("-o")
-        .arg(&export_path)
-        .arg("--force")
-        .assert()
-        .success();
-
-    // Then import it back
-    let mut import_cmd = Command::cargo_bin("etf_analyzer").unwrap();
-    import_cmd
-        .arg("-i")
-        .arg(&export_path)
-        .arg("-f")
         .arg("assets")
+        .arg("--dist-output")
+        .arg(&dist_path)
         .assert()
         .success()
-        .stdout(predicate::str::contains("Total assets:"));
-}
+        .stdout(predicate::str::contains("Asset distribution by ETF count:"))
+        .stdout(predicate::str::contains("Asset distribution saved to:"));
 
-#[test]
-fn test_etf_filter() {
+    let content = fs::read_to_string(&dist_path).unwrap();
+    assert_eq!(content.lines().next().unwrap(), "ETF_Count,Asset_Count");
+
+    // An existing distribution file is only replaced after confirming
+    fs::write(&dist_path, "old").unwrap();
     let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
     cmd.arg("-d")
         .arg("./example-data")
-        .arg("--etfs")
-        .arg("IVW,IWF")
         .arg("-f")
         .arg("assets")
+        .arg("--dist-output")
+        .arg(&dist_path)
+        .write_stdin("n\n")
         .assert()
         .success()
-        .stdout(predicate::str::contains("Total assets:"));
+        .stdout(predicate::str::contains("Export cancelled."));
+    assert_eq!(fs::read_to_string(&dist_path).unwrap(), "old");
 }
 
 #[test]
-fn test_etf_filter_case_insensitive() {
+fn test_assets_function_dist_output_with_rename_out() {
+    let temp_dir = TempDir::new().unwrap();
+    let dist_path = temp_dir.path().join("dist.csv");
+    let output_path = temp_dir.path().join("assets.csv");
+
     let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
     cmd.arg("-d")
         .arg("./example-data")
-        .arg("--etfs")
-        .arg("ivw,iwf")
         .arg("-f")
         .arg("assets")
+        .arg("--rename-out")
+        .arg("Symbol:Ticker")
+        .arg("--dist-output")
+        .arg(&dist_path)
+        .arg("-o")
+        .arg(&output_path)
         .assert()
         .success();
+
+    let dist = fs::read_to_string(&dist_path).unwrap();
+    assert_eq!(dist.lines().next().unwrap(), "ETF_Count,Asset_Count");
+    let assets = fs::read_to_string(&output_path).unwrap();
+    assert!(assets.starts_with("Ticker,"));
 }
 
 #[test]
-fn test_assets_sort_by_count() {
+fn test_assets_function_etf_count_range() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("test_assets.csv");
+
     let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
     cmd.arg("-d")
         .arg("./example-data")
         .arg("-f")
         .arg("assets")
-        .arg("--sort-by")
-        .arg("count")
+        .arg("--min-etfs")
+        .arg("2")
+        .arg("--max-etfs")
+        .arg("3")
+        .arg("-o")
+        .arg(&output_path)
         .assert()
         .success();
+
+    // Every exported asset is held by 2 or 3 ETFs
+    let content = fs::read_to_string(&output_path).unwrap();
+    let mut rdr = csv::Reader::from_reader(content.as_bytes());
+    let mut rows = 0;
+    for record in rdr.records() {
+        let count: u32 = record.unwrap()[2].parse().unwrap();
+        assert!((2..=3).contains(&count));
+        rows += 1;
+    }
+    assert!(rows > 0);
 }
 
 #[test]
-fn test_overlap_function() {
+fn test_build_info_is_json() {
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    let output = cmd.arg("--build-info").output().unwrap();
+    assert!(output.status.success());
+
+    let info: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(info["version"], env!("CARGO_PKG_VERSION"));
+    // Resolved from Cargo.lock, so it is a full version rather than a hard-coded "0.44"
+    let polars_version = info["polars_version"].as_str().unwrap();
+    assert_eq!(polars_version.matches('.').count(), 2, "{}", polars_version);
+    assert!(info["features"].is_array());
+    assert!(info["target"].is_string());
+}
+
+#[test]
+fn test_generate_sample_data() {
+    let temp_dir = TempDir::new().unwrap();
+    let sample_dir = temp_dir.path().join("sample");
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("--generate-sample")
+        .arg(&sample_dir)
+        .assert()
+        .success();
+    assert!(sample_dir.join("alpha-etf-holdings.csv").exists());
+
     let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
     cmd.arg("-d")
-        .arg("./example-data")
+        .arg(&sample_dir)
         .arg("-f")
-        .arg("overlap")
+        .arg("list")
         .assert()
         .success()
-        .stdout(predicate::str::contains("overlapping assets"));
+        .stdout(predicate::str::contains("ALPHA"))
+        .stdout(predicate::str::contains("BETA"))
+        .stdout(predicate::str::contains("GAMMA"));
 }
 
 #[test]
-fn test_overlap_function_with_output() {
+fn test_generate_sample_prompts_before_overwrite() {
     let temp_dir = TempDir::new().unwrap();
-    let output_path = temp_dir.path().join("test_overlap.csv");
+    let alpha_path = temp_dir.path().join("alpha-etf-holdings.csv");
+    fs::write(&alpha_path, "my data").unwrap();
+
+    // Declining keeps the existing file; the other sample files are still written
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("--generate-sample")
+        .arg(temp_dir.path())
+        .write_stdin("n\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("already exists"));
+    assert_eq!(fs::read_to_string(&alpha_path).unwrap(), "my data");
+    assert!(temp_dir.path().join("beta-etf-holdings.csv").exists());
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("--generate-sample")
+        .arg(temp_dir.path())
+        .arg("--force")
+        .assert()
+        .success();
+    assert_ne!(fs::read_to_string(&alpha_path).unwrap(), "my data");
+}
+
+#[test]
+fn test_default_yes_overwrites_on_bare_enter() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("summary.csv");
+    fs::write(&output_path, "old").unwrap();
 
     let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
     cmd.arg("-d")
         .arg("./example-data")
         .arg("-f")
-        .arg("overlap")
+        .arg("summary")
+        .arg("--default-yes")
         .arg("-o")
         .arg(&output_path)
+        .write_stdin("\n")
         .assert()
         .success()
-        .stdout(predicate::str::contains("Overlapping assets saved to:"));
-
-    // Verify the file was created
-    assert!(output_path.exists());
+        .stdout(predicate::str::contains("[Y/n]"));
 
-    // Verify column order: Symbol, Name, ETF_Count, ETFs
     let content = fs::read_to_string(&output_path).unwrap();
-    let first_line = content.lines().next().unwrap();
-    assert_eq!(first_line, "Symbol,Name,ETF_Count,ETFs");
+    assert_ne!(content, "old");
 }
 
 #[test]
-fn test_overlap_function_default_extension() {
+fn test_default_yes_keeps_file_on_empty_stdin() {
     let temp_dir = TempDir::new().unwrap();
-    let output_path = temp_dir.path().join("test_overlap");
+    let output_path = temp_dir.path().join("summary.csv");
+    fs::write(&output_path, "old").unwrap();
 
+    // No input at all (EOF) is not a bare Enter, so --default-yes must not apply
     let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
     cmd.arg("-d")
         .arg("./example-data")
         .arg("-f")
-        .arg("overlap")
+        .arg("summary")
+        .arg("--default-yes")
         .arg("-o")
         .arg(&output_path)
+        .write_stdin("")
         .assert()
-        .success();
+        .success()
+        .stdout(predicate::str::contains("Export cancelled."));
 
-    // Verify the file was created with .csv extension
-    let csv_path = temp_dir.path().join("test_overlap.csv");
-    assert!(csv_path.exists());
+    let content = fs::read_to_string(&output_path).unwrap();
+    assert_eq!(content, "old");
 }
 
 #[test]
-fn test_overlap_sort_by_count() {
+fn test_force_format_only_skips_prompt_for_listed_format() {
+    let temp_dir = TempDir::new().unwrap();
+    let csv_path = temp_dir.path().join("summary.csv");
+    let parquet_path = temp_dir.path().join("summary.parquet");
+    fs::write(&csv_path, "old").unwrap();
+    fs::write(&parquet_path, "old").unwrap();
+
+    // CSV is covered by the policy: overwritten without a prompt
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("-f")
+        .arg("summary")
+        .arg("--force-format")
+        .arg("csv")
+        .arg("-o")
+        .arg(&csv_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Overwrite?").not());
+    assert_ne!(fs::read_to_string(&csv_path).unwrap(), "old");
+
+    // Parquet still prompts; answering no keeps the old file
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("-f")
+        .arg("summary")
+        .arg("--force-format")
+        .arg("csv")
+        .arg("-o")
+        .arg(&parquet_path)
+        .write_stdin("n\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Overwrite?"))
+        .stdout(predicate::str::contains("Export cancelled."));
+    assert_eq!(fs::read_to_string(&parquet_path).unwrap(), "old");
+}
+
+#[test]
+fn test_keep_format_answers_no_without_prompting() {
+    let temp_dir = TempDir::new().unwrap();
+    let parquet_path = temp_dir.path().join("summary.parquet");
+    fs::write(&parquet_path, "old").unwrap();
+
+    // Even a would-be Yes on stdin never reaches a prompt
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("-f")
+        .arg("summary")
+        .arg("--keep-format")
+        .arg("parquet")
+        .arg("--default-yes")
+        .arg("-o")
+        .arg(&parquet_path)
+        .write_stdin("\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Overwrite?").not())
+        .stdout(predicate::str::contains("Export cancelled."));
+    assert_eq!(fs::read_to_string(&parquet_path).unwrap(), "old");
+
+    // --force still wins over the keep policy
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("-f")
+        .arg("summary")
+        .arg("--keep-format")
+        .arg("parquet")
+        .arg("--force")
+        .arg("-o")
+        .arg(&parquet_path)
+        .assert()
+        .success();
+    assert_ne!(fs::read(&parquet_path).unwrap(), b"old");
+}
+
+#[test]
+fn test_padded_symbols_are_trimmed() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("AAA-etf-holdings.csv"),
+        "No.,Symbol,Name,% Weight,Shares\n1,AAPL  ,Apple,60%,100\n2,MSFT,Microsoft,40%,50\n",
+    ).unwrap();
+    fs::write(
+        temp_dir.path().join("BBB-etf-holdings.csv"),
+        "No.,Symbol,Name,% Weight,Shares\n1,AAPL,Apple,70%,100\n2,NVDA,Nvidia,30%,50\n",
+    ).unwrap();
+    let output_path = temp_dir.path().join("assets.csv");
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg(temp_dir.path())
+        .arg("-f")
+        .arg("assets")
+        .arg("-o")
+        .arg(&output_path)
+        .assert()
+        .success();
+
+    // AAPL appears once, held by both ETFs
+    let content = fs::read_to_string(&output_path).unwrap();
+    let aapl: Vec<&str> = content.lines().filter(|l| l.starts_with("AAPL")).collect();
+    assert_eq!(aapl.len(), 1);
+    assert!(aapl[0].contains(",2,"));
+}
+
+#[test]
+fn test_min_holdings_drops_small_etfs() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("TINY-etf-holdings.csv"),
+        "No.,Symbol,Name,% Weight,Shares\n1,AAPL,Apple,100%,10\n",
+    ).unwrap();
+    fs::write(
+        temp_dir.path().join("BIG-etf-holdings.csv"),
+        "No.,Symbol,Name,% Weight,Shares\n1,AAPL,Apple,20%,10\n2,MSFT,Microsoft,20%,10\n3,NVDA,Nvidia,20%,10\n4,JPM,JPMorgan,20%,10\n5,XOM,Exxon,20%,10\n",
+    ).unwrap();
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg(temp_dir.path())
+        .arg("-f")
+        .arg("list")
+        .arg("--min-holdings")
+        .arg("5")
+        .arg("-v")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Dropped 1 ETF(s) with fewer than 5 holdings: TINY"))
+        .stdout(predicate::str::contains("BIG"));
+}
+
+#[test]
+fn test_verbose_weight_column_report() {
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("-f")
+        .arg("list")
+        .arg("-v")
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"Weight column: \d+ percent, \d+ currency, \d+ numeric, \d+ unparseable, \d+ null").unwrap());
+}
+
+#[test]
+fn test_verbose_filter_report() {
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("--etfs")
+        .arg("IVW")
+        .arg("-f")
+        .arg("list")
+        .arg("-v")
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"Filtered from \d+ to \d+ rows \(\d+\.\d% retained\), from \d+ to 1 ETFs").unwrap());
+}
+
+#[test]
+fn test_assets_with_summary_json() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("x.csv");
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("-f")
+        .arg("assets")
+        .arg("-o")
+        .arg(&output_path)
+        .arg("--with-summary-json")
+        .assert()
+        .success();
+
+    assert!(output_path.exists());
+
+    let sidecar = fs::read_to_string(temp_dir.path().join("x.csv.summary.json")).unwrap();
+    let summary: serde_json::Value = serde_json::from_str(&sidecar).unwrap();
+    let content = fs::read_to_string(&output_path).unwrap();
+    assert_eq!(summary["total_assets"], content.lines().count() - 1);
+    assert!(summary["warnings"].is_array());
+
+    // An existing sidecar is only replaced after confirming
+    let sidecar_path = temp_dir.path().join("x.csv.summary.json");
+    fs::write(&sidecar_path, "old").unwrap();
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("-f")
+        .arg("assets")
+        .arg("-o")
+        .arg(&output_path)
+        .arg("--force-format")
+        .arg("csv")
+        .arg("--with-summary-json")
+        .write_stdin("n\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Summary JSON saved to:").not());
+    assert_eq!(fs::read_to_string(&sidecar_path).unwrap(), "old");
+}
+
+#[test]
+fn test_summary_json_requires_output() {
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("-f")
+        .arg("summary")
+        .arg("--with-summary-json")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--with-summary-json requires --output"));
+}
+
+#[test]
+fn test_exclude_synthetic_from_unique() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("AAA-etf-holdings.csv"),
+        "No.,Symbol,Name,% Weight,Shares\n1,AAPL,Apple,60%,100\n2,,Cash,40%,0\n",
+    ).unwrap();
+    fs::write(
+        temp_dir.path().join("BBB-etf-holdings.csv"),
+        "No.,Symbol,Name,% Weight,Shares\n1,AAPL,Apple,70%,100\n2,NVDA,Nvidia,30%,50\n",
+    ).unwrap();
+
+    let run = |exclude: bool| {
+        let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+        cmd.arg("-d").arg(temp_dir.path()).arg("-f").arg("unique");
+        if exclude {
+            cmd.arg("--exclude-synthetic").arg("-v");
+        }
+        cmd.assert().success()
+    };
+
+    // AAA-2 (synthesized) and NVDA are unique without the flag
+    run(false).stdout(predicate::str::contains("Found 2 unique assets"));
+    run(true)
+        .stdout(predicate::str::contains("Excluded 1 synthesized symbol(s)"))
+        .stdout(predicate::str::contains("Found 1 unique assets"));
+}
+
+#[test]
+fn test_output_template() {
+    let temp_dir = TempDir::new().unwrap();
+    let template = format!("{}/{{function}}_{{etfs}}.csv", temp_dir.path().display());
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("--etfs")
+        .arg("IVW,IWF")
+        .arg("-f")
+        .arg("overlap")
+        .arg("--output-template")
+        .arg(&template)
+        .assert()
+        .success();
+
+    assert!(temp_dir.path().join("overlap_IVW-IWF.csv").exists());
+}
+
+#[test]
+fn test_output_template_expands_etf_patterns() {
+    let temp_dir = TempDir::new().unwrap();
+    let template = format!("{}/{{function}}_{{etfs}}.csv", temp_dir.path().display());
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("--etfs")
+        .arg("IV*,IWF")
+        .arg("-f")
+        .arg("overlap")
+        .arg("--output-template")
+        .arg(&template)
+        .assert()
+        .success();
+
+    assert!(temp_dir.path().join("overlap_IVW-IWF.csv").exists());
+}
+
+#[test]
+fn test_unique_function() {
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("-f")
+        .arg("unique")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("unique assets"));
+}
+
+#[test]
+fn test_unique_function_without_weight_column() {
+    let temp_dir = TempDir::new().unwrap();
+    let data_dir = temp_dir.path().join("data");
+    fs::create_dir(&data_dir).unwrap();
+    fs::write(
+        data_dir.join("bond-etf-holdings.csv"),
+        "No.,Symbol,Name,Shares\n1,T1,Treasury 2030,100\n",
+    ).unwrap();
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg(&data_dir)
+        .arg("-f")
+        .arg("unique")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Weight column required for unique assets"));
+}
+
+#[test]
+fn test_unique_function_with_output() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("test_unique.csv");
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("-f")
+        .arg("unique")
+        .arg("-o")
+        .arg(&output_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Unique assets saved to:"));
+
+    // Verify the file was created
+    assert!(output_path.exists());
+
+    // Verify column order: Symbol, Name, Weight, ETF
+    let content = fs::read_to_string(&output_path).unwrap();
+    let first_line = content.lines().next().unwrap();
+    assert_eq!(first_line, "Symbol,Name,Weight,ETF");
+}
+
+#[test]
+fn test_export_function_csv() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("test_export.csv");
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("-f")
+        .arg("export")
+        .arg("-o")
+        .arg(&output_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Successfully exported to:"));
+
+    // Verify the file was created
+    assert!(output_path.exists());
+
+    // Verify it's a valid CSV with expected columns
+    let content = fs::read_to_string(&output_path).unwrap();
+    assert!(content.contains("ETF,Symbol,Name,Weight"));
+}
+
+#[test]
+fn test_export_function_add_pct_column() {
+    let temp_dir = TempDir::new().unwrap();
+    let data_dir = temp_dir.path().join("data");
+    fs::create_dir(&data_dir).unwrap();
+    fs::write(
+        data_dir.join("test-etf-holdings.csv"),
+        "No.,Symbol,Name,% Weight,Shares\n1,AAPL,Apple Inc.,5%,100\n",
+    ).unwrap();
+    let output_path = temp_dir.path().join("test_export.csv");
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg(&data_dir)
+        .arg("-f")
+        .arg("export")
+        .arg("--add-pct-column")
+        .arg("-o")
+        .arg(&output_path)
+        .assert()
+        .success();
+
+    let content = fs::read_to_string(&output_path).unwrap();
+    let mut lines = content.lines();
+    assert!(lines.next().unwrap().ends_with(",Weight_Pct"));
+    assert!(lines.next().unwrap().ends_with(",5.00%"));
+}
+
+#[test]
+fn test_export_function_add_rank_column() {
+    let temp_dir = TempDir::new().unwrap();
+    let data_dir = temp_dir.path().join("data");
+    fs::create_dir(&data_dir).unwrap();
+    fs::write(
+        data_dir.join("test-etf-holdings.csv"),
+        "No.,Symbol,Name,% Weight,Shares\n1,MSFT,Microsoft,4%,100\n2,AAPL,Apple Inc.,5%,100\n",
+    ).unwrap();
+    let output_path = temp_dir.path().join("test_export.csv");
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg(&data_dir)
+        .arg("-f")
+        .arg("export")
+        .arg("--add-rank-column")
+        .arg("-o")
+        .arg(&output_path)
+        .assert()
+        .success();
+
+    let content = fs::read_to_string(&output_path).unwrap();
+    let lines: Vec<&str> = content.lines().collect();
+    assert!(lines[0].ends_with(",Rank_In_ETF"));
+    assert!(lines[1].contains("MSFT") && lines[1].ends_with(",2"));
+    assert!(lines[2].contains("AAPL") && lines[2].ends_with(",1"));
+}
+
+#[test]
+fn test_export_into_data_dir_warns() {
+    let temp_dir = TempDir::new().unwrap();
+    let data_dir = temp_dir.path().join("data");
+    fs::create_dir(&data_dir).unwrap();
+    fs::write(
+        data_dir.join("test-etf-holdings.csv"),
+        "No.,Symbol,Name,% Weight,Shares\n1,AAPL,Apple Inc.,5%,100\n",
+    ).unwrap();
+    let output_path = data_dir.join("out-etf-holdings.csv");
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg(&data_dir)
+        .arg("-f")
+        .arg("export")
+        .arg("-o")
+        .arg(&output_path)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Warning: Output"))
+        .stderr(predicate::str::contains("inside the data directory"));
+}
+
+#[test]
+fn test_export_into_data_dir_strict_fails() {
+    let temp_dir = TempDir::new().unwrap();
+    let data_dir = temp_dir.path().join("data");
+    fs::create_dir(&data_dir).unwrap();
+    fs::write(
+        data_dir.join("test-etf-holdings.csv"),
+        "No.,Symbol,Name,% Weight,Shares\n1,AAPL,Apple Inc.,5%,100\n",
+    ).unwrap();
+    let output_path = data_dir.join("out-etf-holdings.csv");
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg(&data_dir)
+        .arg("-f")
+        .arg("export")
+        .arg("--strict")
+        .arg("-o")
+        .arg(&output_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("inside the data directory"));
+
+    assert!(!output_path.exists());
+}
+
+#[test]
+fn test_export_function_null_value() {
+    let temp_dir = TempDir::new().unwrap();
+    let data_dir = temp_dir.path().join("data");
+    fs::create_dir(&data_dir).unwrap();
+    fs::write(
+        data_dir.join("test-etf-holdings.csv"),
+        "No.,Symbol,Name,% Weight,Shares\n1,AAPL,Apple Inc.,5%,100\n2,MSFT,Microsoft,,200\n",
+    ).unwrap();
+    let output_path = temp_dir.path().join("test_export.csv");
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg(&data_dir)
+        .arg("-f")
+        .arg("export")
+        .arg("--null-value")
+        .arg("NA")
+        .arg("-o")
+        .arg(&output_path)
+        .assert()
+        .success();
+
+    let content = fs::read_to_string(&output_path).unwrap();
+    assert!(content.contains("TEST,MSFT,Microsoft,NA,200"));
+}
+
+#[test]
+fn test_export_function_parquet() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("test_export.parquet");
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("-f")
+        .arg("export")
+        .arg("-o")
+        .arg(&output_path)
+        .assert()
+        .success();
+
+    // Verify the file was created
+    assert!(output_path.exists());
+}
+
+#[test]
+fn test_import_function() {
+    let temp_dir = TempDir::new().unwrap();
+    let export_path = temp_dir.path().join("test_export.parquet");
+
+    // First export data
+    let mut export_cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    export_cmd
+        .arg("-d")
+        .arg("./example-data")
+        .arg("-f")
+        .arg("export")
+        .arg("-o")
+        .arg(&export_path)
+        .arg("--force")
+        .assert()
+        .success();
+
+    // Then import it back
+    let mut import_cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    import_cmd
+        .arg("-i")
+        .arg(&export_path)
+        .arg("-f")
+        .arg("assets")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Total assets:"));
+}
+
+#[test]
+fn test_explicit_file_list() {
+    let temp_dir = TempDir::new().unwrap();
+    let first = temp_dir.path().join("first.csv");
+    let second = temp_dir.path().join("picks").join("second-etf-holdings.csv");
+    fs::create_dir(second.parent().unwrap()).unwrap();
+    fs::write(&first, "No.,Symbol,Name,% Weight,Shares\n1,AAPL,Apple Inc.,5%,100\n").unwrap();
+    fs::write(&second, "No.,Symbol,Name,% Weight,Shares\n1,MSFT,Microsoft,6%,100\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("--files")
+        .arg(format!("{},{}", first.display(), second.display()))
+        .arg("-f")
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Found 2 ETFs"))
+        .stdout(predicate::str::contains("FIRST"))
+        .stdout(predicate::str::contains("SECOND"));
+}
+
+#[test]
+fn test_stable_etf_order_follows_file_list() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("etfs.txt");
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("--files")
+        .arg("./example-data/vtv-etf-holdings.csv,./example-data/ivw-etf-holdings.csv,./example-data/corn-etf-holdings.csv")
+        .arg("-f")
+        .arg("list")
+        .arg("--stable-etf-order")
+        .arg("-o")
+        .arg(&output_path)
+        .assert()
+        .success();
+
+    let content = fs::read_to_string(&output_path).unwrap();
+    assert_eq!(content, "VTV\nIVW\nCORN\n");
+}
+
+#[test]
+fn test_explicit_file_list_missing_file() {
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("--files")
+        .arg("./example-data/ivw-etf-holdings.csv,./missing-etf-holdings.csv")
+        .arg("-f")
+        .arg("list")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Holdings file(s) not found: ./missing-etf-holdings.csv"));
+}
+
+#[test]
+fn test_etf_filter() {
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("--etfs")
+        .arg("IVW,IWF")
+        .arg("-f")
+        .arg("assets")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Total assets:"));
+}
+
+#[test]
+fn test_etf_filter_case_insensitive() {
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("--etfs")
+        .arg("ivw,iwf")
+        .arg("-f")
+        .arg("assets")
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_assets_sort_by_count() {
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("-f")
+        .arg("assets")
+        .arg("--sort-by")
+        .arg("count")
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_overlap_function() {
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("-f")
+        .arg("overlap")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("overlapping assets"));
+}
+
+#[test]
+fn test_overlap_function_with_output() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("test_overlap.csv");
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("-f")
+        .arg("overlap")
+        .arg("-o")
+        .arg(&output_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Overlapping assets saved to:"));
+
+    // Verify the file was created
+    assert!(output_path.exists());
+
+    // Verify column order: Symbol, Name, ETF_Count, ETFs
+    let content = fs::read_to_string(&output_path).unwrap();
+    let first_line = content.lines().next().unwrap();
+    assert_eq!(first_line, "Symbol,Name,ETF_Count,ETFs");
+}
+
+#[test]
+fn test_overlap_function_default_extension() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("test_overlap");
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("-f")
+        .arg("overlap")
+        .arg("-o")
+        .arg(&output_path)
+        .assert()
+        .success();
+
+    // Verify the file was created with .csv extension
+    let csv_path = temp_dir.path().join("test_overlap.csv");
+    assert!(csv_path.exists());
+}
+
+#[test]
+fn test_overlap_sort_by_count() {
     let temp_dir = TempDir::new().unwrap();
     let output_path = temp_dir.path().join("test_overlap_count.csv");
 
@@ -304,385 +1233,942 @@ fn test_overlap_sort_by_count() {
     cmd.arg("-d")
         .arg("./example-data")
         .arg("-f")
-        .arg("overlap")
-        .arg("--sort-by")
-        .arg("count")
+        .arg("overlap")
+        .arg("--sort-by")
+        .arg("count")
+        .arg("-o")
+        .arg(&output_path)
+        .assert()
+        .success();
+
+    // Verify the file was created
+    assert!(output_path.exists());
+
+    // Read the file and verify sorting (first data row should have highest count)
+    let content = fs::read_to_string(&output_path).unwrap();
+    let lines: Vec<&str> = content.lines().collect();
+    assert!(lines.len() > 2); // Header + at least 2 data rows
+
+    // Parse second line (first data row) to get ETF_Count
+    let first_data = lines[1].split(',').collect::<Vec<&str>>();
+    let first_count: u32 = first_data[2].parse().unwrap();
+
+    // Parse last line to get ETF_Count
+    let last_data = lines[lines.len() - 1].split(',').collect::<Vec<&str>>();
+    let last_count: u32 = last_data[2].parse().unwrap();
+
+    // Verify descending order by count
+    assert!(first_count >= last_count);
+}
+
+#[test]
+fn test_mapping_function() {
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("-f")
+        .arg("mapping")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Total assets:"))
+        .stdout(predicate::str::contains("Asset distribution by ETF count:"));
+}
+
+#[test]
+fn test_mapping_function_with_output() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("test_mapping.csv");
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("-f")
+        .arg("mapping")
+        .arg("-o")
+        .arg(&output_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Asset mapping saved to:"));
+
+    // Verify the file was created
+    assert!(output_path.exists());
+
+    // Verify column order: Symbol, Name, ETF_Count, ETFs
+    let content = fs::read_to_string(&output_path).unwrap();
+    let first_line = content.lines().next().unwrap();
+    assert_eq!(first_line, "Symbol,Name,ETF_Count,ETFs");
+}
+
+#[test]
+fn test_mapping_function_default_extension() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("test_mapping");
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("-f")
+        .arg("mapping")
+        .arg("-o")
+        .arg(&output_path)
+        .assert()
+        .success();
+
+    // Verify the file was created with .csv extension
+    let csv_path = temp_dir.path().join("test_mapping.csv");
+    assert!(csv_path.exists());
+}
+
+#[test]
+fn test_mapping_sort_by_count() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("test_mapping_count.csv");
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("-f")
+        .arg("mapping")
+        .arg("--sort-by")
+        .arg("count")
+        .arg("-o")
+        .arg(&output_path)
+        .assert()
+        .success();
+
+    // Verify the file was created
+    assert!(output_path.exists());
+
+    // Read the file and verify sorting (first data row should have highest count)
+    let content = fs::read_to_string(&output_path).unwrap();
+    let lines: Vec<&str> = content.lines().collect();
+    assert!(lines.len() > 2); // Header + at least 2 data rows
+
+    // Parse second line (first data row) to get ETF_Count
+    let first_data = lines[1].split(',').collect::<Vec<&str>>();
+    let first_count: u32 = first_data[2].parse().unwrap();
+
+    // Parse last line to get ETF_Count
+    let last_data = lines[lines.len() - 1].split(',').collect::<Vec<&str>>();
+    let last_count: u32 = last_data[2].parse().unwrap();
+
+    // Verify descending order by count
+    assert!(first_count >= last_count);
+}
+
+#[test]
+fn test_list_function() {
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("-f")
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Found"))
+        .stdout(predicate::str::contains("ETFs:"));
+}
+
+#[test]
+fn test_list_function_with_output() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("test_list.txt");
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("-f")
+        .arg("list")
+        .arg("-o")
+        .arg(&output_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("ETF list saved to:"));
+
+    // Verify the file was created
+    assert!(output_path.exists());
+
+    // Verify the file has content (one ETF per line)
+    let content = fs::read_to_string(&output_path).unwrap();
+    let lines: Vec<&str> = content.lines().collect();
+    assert!(lines.len() > 0);
+
+    // Verify ETF names are in the file
+    assert!(content.len() > 0);
+}
+
+#[test]
+fn test_list_function_default_extension() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("test_list");
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("-f")
+        .arg("list")
+        .arg("-o")
+        .arg(&output_path)
+        .assert()
+        .success();
+
+    // Verify the file was created with .txt extension
+    let txt_path = temp_dir.path().join("test_list.txt");
+    assert!(txt_path.exists());
+}
+
+#[test]
+fn test_list_function_with_filter() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("test_list_filter.txt");
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("--etfs")
+        .arg("IVW,IWF")
+        .arg("-f")
+        .arg("list")
+        .arg("-o")
+        .arg(&output_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Found 2 ETFs:"));
+
+    // Verify the file was created
+    assert!(output_path.exists());
+
+    // Verify content contains exactly 2 ETFs
+    let content = fs::read_to_string(&output_path).unwrap();
+    let lines: Vec<&str> = content.lines().collect();
+    assert_eq!(lines.len(), 2);
+
+    // Verify the ETFs are IVW and IWF (sorted alphabetically)
+    assert!(lines.contains(&"IVW"));
+    assert!(lines.contains(&"IWF"));
+}
+
+#[test]
+fn test_summary_function() {
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("-f")
+        .arg("summary")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Total ETFs:"))
+        .stdout(predicate::str::contains("Largest ETF contains"))
+        .stdout(predicate::str::contains("Smallest ETF contains"));
+}
+
+#[test]
+fn test_summary_function_with_output() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("test_summary.csv");
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("-f")
+        .arg("summary")
+        .arg("-o")
+        .arg(&output_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("ETF summary saved to:"));
+
+    // Verify the file was created
+    assert!(output_path.exists());
+
+    // Verify column order: ETF, Asset_Count, Assets
+    let content = fs::read_to_string(&output_path).unwrap();
+    let first_line = content.lines().next().unwrap();
+    assert_eq!(first_line, "ETF,Asset_Count,Assets");
+}
+
+#[test]
+fn test_summary_function_default_extension() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("test_summary");
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("-f")
+        .arg("summary")
+        .arg("-o")
+        .arg(&output_path)
+        .assert()
+        .success();
+
+    // Verify the file was created with .csv extension
+    let csv_path = temp_dir.path().join("test_summary.csv");
+    assert!(csv_path.exists());
+}
+
+#[test]
+fn test_summary_function_with_filter() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("test_summary_filter.csv");
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("--etfs")
+        .arg("IVW,IWF")
+        .arg("-f")
+        .arg("summary")
         .arg("-o")
         .arg(&output_path)
         .assert()
-        .success();
+        .success()
+        .stdout(predicate::str::contains("Total ETFs: 2"));
 
     // Verify the file was created
     assert!(output_path.exists());
 
-    // Read the file and verify sorting (first data row should have highest count)
+    // Verify content contains exactly 2 ETFs (+ header)
     let content = fs::read_to_string(&output_path).unwrap();
     let lines: Vec<&str> = content.lines().collect();
-    assert!(lines.len() > 2); // Header + at least 2 data rows
+    assert_eq!(lines.len(), 3); // Header + 2 ETFs
+}
 
-    // Parse second line (first data row) to get ETF_Count
-    let first_data = lines[1].split(',').collect::<Vec<&str>>();
-    let first_count: u32 = first_data[2].parse().unwrap();
+#[test]
+fn test_compare_function() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("test_compare.csv");
 
-    // Parse last line to get ETF_Count
-    let last_data = lines[lines.len() - 1].split(',').collect::<Vec<&str>>();
-    let last_count: u32 = last_data[2].parse().unwrap();
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("--etfs")
+        .arg("IVW,IWF")
+        .arg("-f")
+        .arg("compare")
+        .arg("-o")
+        .arg(&output_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Comparing 2 ETFs"));
 
-    // Verify descending order by count
-    assert!(first_count >= last_count);
+    // Verify the file was created
+    assert!(output_path.exists());
+
+    // Verify column structure: Symbol, IVW, IWF
+    let content = fs::read_to_string(&output_path).unwrap();
+    let first_line = content.lines().next().unwrap();
+    assert_eq!(first_line, "Symbol,IVW,IWF");
+}
+
+#[test]
+fn test_compare_function_min_present() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("test_compare.csv");
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("--etfs")
+        .arg("IVW,IWF,VTV")
+        .arg("-f")
+        .arg("compare")
+        .arg("--min-present")
+        .arg("2")
+        .arg("-o")
+        .arg(&output_path)
+        .assert()
+        .success();
+
+    // Every remaining symbol is held by at least two of the three ETFs
+    let content = fs::read_to_string(&output_path).unwrap();
+    let rows: Vec<&str> = content.lines().skip(1).collect();
+    assert!(!rows.is_empty());
+    for row in rows {
+        assert!(row.matches("N/A").count() <= 1, "{}", row);
+    }
+}
+
+#[test]
+fn test_compare_function_default_extension() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("test_compare");
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("--etfs")
+        .arg("IVW,IWF")
+        .arg("-f")
+        .arg("compare")
+        .arg("-o")
+        .arg(&output_path)
+        .assert()
+        .success();
+
+    // Verify the file was created with .csv extension
+    let csv_path = temp_dir.path().join("test_compare.csv");
+    assert!(csv_path.exists());
+}
+
+#[test]
+fn test_compare_function_requires_etfs() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("test_compare.csv");
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("-f")
+        .arg("compare")
+        .arg("-o")
+        .arg(&output_path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Compare function requires --etfs to be specified"));
+}
+
+#[test]
+fn test_compare_function_requires_output() {
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("--etfs")
+        .arg("IVW,IWF")
+        .arg("-f")
+        .arg("compare")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Compare function requires --output (-o) to be specified"));
+}
+
+#[test]
+fn test_profile_function() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("test_profile");
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("--etfs")
+        .arg("IWF")
+        .arg("-f")
+        .arg("profile")
+        .arg("-o")
+        .arg(&output_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Weight profile for IWF"))
+        .stdout(predicate::str::contains("Top 10 holdings make up"));
+
+    // Verify default .csv extension and column structure
+    let csv_path = temp_dir.path().join("test_profile.csv");
+    let content = fs::read_to_string(&csv_path).unwrap();
+    let first_line = content.lines().next().unwrap();
+    assert_eq!(first_line, "Rank,Symbol,Weight,Cumulative_Weight");
+}
+
+#[test]
+fn test_profile_function_requires_single_etf() {
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("--etfs")
+        .arg("IVW,IWF")
+        .arg("-f")
+        .arg("profile")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Profile function requires exactly one ETF in --etfs"));
+}
+
+#[test]
+fn test_avg_overlap_function() {
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    let output = cmd.arg("-d")
+        .arg("./example-data")
+        .arg("--etfs")
+        .arg("IVW,IWF")
+        .arg("-f")
+        .arg("avg-overlap")
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+
+    // Output is a single number between 0 and 1
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let value: f64 = stdout.trim().parse().unwrap();
+    assert!(value > 0.0 && value <= 1.0);
+}
+
+#[test]
+fn test_overlap_pairs_function() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("test_pairs.csv");
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("-f")
+        .arg("overlap-pairs")
+        .arg("--top")
+        .arg("3")
+        .arg("-o")
+        .arg(&output_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Top 3 overlapping ETF pairs:"));
+
+    let content = fs::read_to_string(&output_path).unwrap();
+    let lines: Vec<&str> = content.lines().collect();
+    assert_eq!(lines[0], "ETF_A,ETF_B,Overlap");
+    assert_eq!(lines.len(), 4); // Header + 3 pairs
+}
+
+#[test]
+fn test_overlap_external_function() {
+    let temp_dir = TempDir::new().unwrap();
+    let data_dir = temp_dir.path().join("data");
+    fs::create_dir(&data_dir).unwrap();
+    fs::write(
+        data_dir.join("CORE-etf-holdings.csv"),
+        "No.,Symbol,Name,% Weight,Shares\n1,AAPL,Apple,60%,10\n2,MSFT,Microsoft,40%,10\n",
+    ).unwrap();
+    fs::write(
+        data_dir.join("NICHE-etf-holdings.csv"),
+        "No.,Symbol,Name,% Weight,Shares\n1,TSLA,Tesla,90%,10\n2,AAPL,Apple,10%,10\n",
+    ).unwrap();
+    let reference = temp_dir.path().join("sp500.csv");
+    fs::write(
+        &reference,
+        "No.,Symbol,Name,% Weight,Shares\n1,AAPL,Apple,50%,10\n2,MSFT,Microsoft,30%,10\n3,NVDA,Nvidia,20%,10\n",
+    ).unwrap();
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg(&data_dir)
+        .arg("-f")
+        .arg("overlap-external")
+        .arg("--reference")
+        .arg(&reference)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("CORE: 80.00%"))
+        .stdout(predicate::str::contains("NICHE: 10.00%"));
+}
+
+#[test]
+fn test_unique_exposure_function() {
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("--etfs")
+        .arg("IVW,IWF")
+        .arg("-f")
+        .arg("unique-exposure")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Unique exposure by ETF:"))
+        .stdout(predicate::str::contains("IVW:"))
+        .stdout(predicate::str::contains("IWF:"));
+}
+
+#[test]
+fn test_jaccard_matrix_function() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("jaccard.csv");
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("--etfs")
+        .arg("IVW,IWF")
+        .arg("-f")
+        .arg("jaccard-matrix")
+        .arg("-o")
+        .arg(&output_path)
+        .assert()
+        .success();
+
+    let content = fs::read_to_string(&output_path).unwrap();
+    let lines: Vec<&str> = content.lines().collect();
+    assert_eq!(lines[0], "ETF,IVW,IWF");
+    assert_eq!(lines.len(), 3); // Header + one row per ETF
+}
+
+#[test]
+fn test_pair_function() {
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("--etfs")
+        .arg("IVW,IWF")
+        .arg("-f")
+        .arg("pair")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Only in IVW:"))
+        .stdout(predicate::str::contains("Only in IWF:"))
+        .stdout(predicate::str::contains("In both:"));
+}
+
+#[test]
+fn test_weight_diff_function() {
+    let temp_dir = TempDir::new().unwrap();
+    let output_path = temp_dir.path().join("diff.csv");
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("--etfs")
+        .arg("IVW,IWF")
+        .arg("-f")
+        .arg("weight-diff")
+        .arg("-o")
+        .arg(&output_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Weight differences (IVW - IWF):"));
+
+    let content = fs::read_to_string(&output_path).unwrap();
+    assert!(content.starts_with("Symbol,Weight_A,Weight_B,Diff"));
+}
+
+#[test]
+fn test_pair_function_requires_two_etfs() {
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.arg("-d")
+        .arg("./example-data")
+        .arg("-f")
+        .arg("pair")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("exactly two ETFs"));
 }
 
 #[test]
-fn test_mapping_function() {
+fn test_size_categories_function() {
     let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
     cmd.arg("-d")
         .arg("./example-data")
         .arg("-f")
-        .arg("mapping")
+        .arg("size-categories")
+        .arg("--size-edges")
+        .arg("20,50,100")
         .assert()
         .success()
-        .stdout(predicate::str::contains("Total assets:"))
-        .stdout(predicate::str::contains("Asset distribution by ETF count:"));
+        .stdout(predicate::str::contains("ETF size categories:"))
+        .stdout(predicate::str::contains("CORN: Micro"));
 }
 
 #[test]
-fn test_mapping_function_with_output() {
+fn test_synthetic_function() {
     let temp_dir = TempDir::new().unwrap();
-    let output_path = temp_dir.path().join("test_mapping.csv");
+    fs::write(
+        temp_dir.path().join("CMDTY-etf-holdings.csv"),
+        "No.,Symbol,Name,% Weight,Shares\n1,,Corn Future,50%,10\n2,GLD,Gold Trust,50%,5\n",
+    ).unwrap();
+    fs::write(
+        temp_dir.path().join("SPY-etf-holdings.csv"),
+        "No.,Symbol,Name,% Weight,Shares\n1,AAPL,Apple,60%,100\n2,MSFT,Microsoft,40%,50\n",
+    ).unwrap();
 
     let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
     cmd.arg("-d")
-        .arg("./example-data")
+        .arg(temp_dir.path())
         .arg("-f")
-        .arg("mapping")
-        .arg("-o")
-        .arg(&output_path)
+        .arg("synthetic")
         .assert()
         .success()
-        .stdout(predicate::str::contains("Asset mapping saved to:"));
-
-    // Verify the file was created
-    assert!(output_path.exists());
-
-    // Verify column order: Symbol, Name, ETF_Count, ETFs
-    let content = fs::read_to_string(&output_path).unwrap();
-    let first_line = content.lines().next().unwrap();
-    assert_eq!(first_line, "Symbol,Name,ETF_Count,ETFs");
+        .stdout(predicate::str::contains("CMDTY: 1 of 2 (50.0%) [flagged]"))
+        .stdout(predicate::str::contains("SPY: 0 of 2 (0.0%)\n"));
 }
 
 #[test]
-fn test_mapping_function_default_extension() {
+fn test_drawdowns_function() {
     let temp_dir = TempDir::new().unwrap();
-    let output_path = temp_dir.path().join("test_mapping");
+    let prices_path = temp_dir.path().join("prices.csv");
+    fs::write(&prices_path, "Date,SPY,QQQ\n2024-01-01,100,50\n2024-01-02,120,40\n2024-01-03,90,60\n2024-01-04,110,54\n").unwrap();
 
     let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
-    cmd.arg("-d")
-        .arg("./example-data")
+    cmd.arg("-i")
+        .arg(&prices_path)
         .arg("-f")
-        .arg("mapping")
-        .arg("-o")
-        .arg(&output_path)
+        .arg("drawdowns")
         .assert()
-        .success();
-
-    // Verify the file was created with .csv extension
-    let csv_path = temp_dir.path().join("test_mapping.csv");
-    assert!(csv_path.exists());
+        .success()
+        .stdout(predicate::str::contains("SPY: 25.00%"))
+        .stdout(predicate::str::contains("QQQ: 20.00%"));
 }
 
 #[test]
-fn test_mapping_sort_by_count() {
-    let temp_dir = TempDir::new().unwrap();
-    let output_path = temp_dir.path().join("test_mapping_count.csv");
-
+fn test_drawdowns_requires_price_cols_for_holdings() {
     let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
     cmd.arg("-d")
         .arg("./example-data")
         .arg("-f")
-        .arg("mapping")
-        .arg("--sort-by")
-        .arg("count")
-        .arg("-o")
-        .arg(&output_path)
+        .arg("drawdowns")
         .assert()
-        .success();
-
-    // Verify the file was created
-    assert!(output_path.exists());
-
-    // Read the file and verify sorting (first data row should have highest count)
-    let content = fs::read_to_string(&output_path).unwrap();
-    let lines: Vec<&str> = content.lines().collect();
-    assert!(lines.len() > 2); // Header + at least 2 data rows
-
-    // Parse second line (first data row) to get ETF_Count
-    let first_data = lines[1].split(',').collect::<Vec<&str>>();
-    let first_count: u32 = first_data[2].parse().unwrap();
-
-    // Parse last line to get ETF_Count
-    let last_data = lines[lines.len() - 1].split(',').collect::<Vec<&str>>();
-    let last_count: u32 = last_data[2].parse().unwrap();
+        .failure()
+        .stderr(predicate::str::contains("requires --price-cols"));
+}
 
-    // Verify descending order by count
-    assert!(first_count >= last_count);
+#[test]
+fn test_head_function_prints_top_rows() {
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    let output = cmd
+        .arg("-d")
+        .arg("./example-data")
+        .arg("-f")
+        .arg("head")
+        .arg("--top")
+        .arg("3")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    // Data rows follow the header separator line of the table
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let data_rows = stdout
+        .lines()
+        .skip_while(|line| !line.starts_with('╞'))
+        .filter(|line| line.starts_with('│'))
+        .count();
+    assert_eq!(data_rows, 3);
 }
 
 #[test]
-fn test_list_function() {
+fn test_tail_function_respects_etf_filter() {
     let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
     cmd.arg("-d")
         .arg("./example-data")
+        .arg("--etfs")
+        .arg("IWF")
         .arg("-f")
-        .arg("list")
+        .arg("tail")
+        .arg("--top")
+        .arg("2")
         .assert()
         .success()
-        .stdout(predicate::str::contains("Found"))
-        .stdout(predicate::str::contains("ETFs:"));
+        .stdout(predicate::str::contains("shape: (2,"))
+        .stdout(predicate::str::contains("CORN").not());
 }
 
 #[test]
-fn test_list_function_with_output() {
+fn test_portfolio_return_function() {
     let temp_dir = TempDir::new().unwrap();
-    let output_path = temp_dir.path().join("test_list.txt");
+    let returns_path = temp_dir.path().join("returns.csv");
+    fs::write(&returns_path, "ETF,Return\nIVW,0.10\nIWF,5%\nVTV,0.50\n").unwrap();
 
     let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
     cmd.arg("-d")
         .arg("./example-data")
+        .arg("--etfs")
+        .arg("IVW,IWF")
         .arg("-f")
-        .arg("list")
-        .arg("-o")
-        .arg(&output_path)
+        .arg("portfolio-return")
+        .arg("--returns-file")
+        .arg(&returns_path)
         .assert()
         .success()
-        .stdout(predicate::str::contains("ETF list saved to:"));
-
-    // Verify the file was created
-    assert!(output_path.exists());
-
-    // Verify the file has content (one ETF per line)
-    let content = fs::read_to_string(&output_path).unwrap();
-    let lines: Vec<&str> = content.lines().collect();
-    assert!(lines.len() > 0);
-
-    // Verify ETF names are in the file
-    assert!(content.len() > 0);
-}
-
-#[test]
-fn test_list_function_default_extension() {
-    let temp_dir = TempDir::new().unwrap();
-    let output_path = temp_dir.path().join("test_list");
+        .stdout(predicate::str::contains("Portfolio return: 7.50%"));
 
+    // VBR has no return in the file
     let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
     cmd.arg("-d")
         .arg("./example-data")
+        .arg("--etfs")
+        .arg("IVW,VBR")
         .arg("-f")
-        .arg("list")
-        .arg("-o")
-        .arg(&output_path)
+        .arg("portfolio-return")
+        .arg("--returns-file")
+        .arg(&returns_path)
         .assert()
-        .success();
-
-    // Verify the file was created with .txt extension
-    let txt_path = temp_dir.path().join("test_list.txt");
-    assert!(txt_path.exists());
+        .failure();
 }
 
 #[test]
-fn test_list_function_with_filter() {
+fn test_ubiquity_function() {
     let temp_dir = TempDir::new().unwrap();
-    let output_path = temp_dir.path().join("test_list_filter.txt");
+    let output_path = temp_dir.path().join("ubiquity.csv");
 
     let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
     cmd.arg("-d")
         .arg("./example-data")
-        .arg("--etfs")
-        .arg("IVW,IWF")
         .arg("-f")
-        .arg("list")
+        .arg("ubiquity")
+        .arg("--top")
+        .arg("3")
         .arg("-o")
         .arg(&output_path)
         .assert()
         .success()
-        .stdout(predicate::str::contains("Found 2 ETFs:"));
-
-    // Verify the file was created
-    assert!(output_path.exists());
+        .stdout(predicate::str::contains("Top 3 assets by total weight across ETFs:"));
 
-    // Verify content contains exactly 2 ETFs
     let content = fs::read_to_string(&output_path).unwrap();
-    let lines: Vec<&str> = content.lines().collect();
-    assert_eq!(lines.len(), 2);
-
-    // Verify the ETFs are IVW and IWF (sorted alphabetically)
-    assert!(lines.contains(&"IVW"));
-    assert!(lines.contains(&"IWF"));
+    assert_eq!(content.lines().next().unwrap(), "Symbol,ETF_Count,Total_Weight");
 }
 
 #[test]
-fn test_summary_function() {
+fn test_weight_sum_function() {
     let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
     cmd.arg("-d")
         .arg("./example-data")
+        .arg("--etfs")
+        .arg("IVW")
         .arg("-f")
-        .arg("summary")
+        .arg("weight-sum")
         .assert()
         .success()
-        .stdout(predicate::str::contains("Total ETFs:"))
-        .stdout(predicate::str::contains("Largest ETF contains"))
-        .stdout(predicate::str::contains("Smallest ETF contains"));
+        .stdout(predicate::str::contains("Weight sum by ETF:"))
+        .stdout(predicate::str::contains("IVW:"));
 }
 
 #[test]
-fn test_summary_function_with_output() {
+fn test_gini_function() {
     let temp_dir = TempDir::new().unwrap();
-    let output_path = temp_dir.path().join("test_summary.csv");
+    let output_path = temp_dir.path().join("gini.csv");
 
     let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
     cmd.arg("-d")
         .arg("./example-data")
+        .arg("--etfs")
+        .arg("IVW")
         .arg("-f")
-        .arg("summary")
+        .arg("gini")
         .arg("-o")
         .arg(&output_path)
         .assert()
         .success()
-        .stdout(predicate::str::contains("ETF summary saved to:"));
+        .stdout(predicate::str::contains("Gini coefficient by ETF:"))
+        .stdout(predicate::str::contains("IVW:"));
 
-    // Verify the file was created
-    assert!(output_path.exists());
-
-    // Verify column order: ETF, Asset_Count, Assets
     let content = fs::read_to_string(&output_path).unwrap();
-    let first_line = content.lines().next().unwrap();
-    assert_eq!(first_line, "ETF,Asset_Count,Assets");
+    assert_eq!(content.lines().next().unwrap(), "ETF,Gini");
 }
 
 #[test]
-fn test_summary_function_default_extension() {
-    let temp_dir = TempDir::new().unwrap();
-    let output_path = temp_dir.path().join("test_summary");
-
+fn test_common_assets_function() {
     let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
     cmd.arg("-d")
         .arg("./example-data")
         .arg("-f")
-        .arg("summary")
-        .arg("-o")
-        .arg(&output_path)
+        .arg("common-assets")
+        .arg("--top")
+        .arg("5")
         .assert()
-        .success();
-
-    // Verify the file was created with .csv extension
-    let csv_path = temp_dir.path().join("test_summary.csv");
-    assert!(csv_path.exists());
+        .success()
+        .stdout(predicate::str::contains("Top 5 most common assets:"));
 }
 
 #[test]
-fn test_summary_function_with_filter() {
+fn test_search_function_multiple_queries() {
     let temp_dir = TempDir::new().unwrap();
-    let output_path = temp_dir.path().join("test_summary_filter.csv");
+    fs::write(
+        temp_dir.path().join("SOXX-etf-holdings.csv"),
+        "No.,Symbol,Name,% Weight,Shares\n1,NVDA,Nvidia Semiconductor,40%,10\n2,TSM,Taiwan Semiconductor Chip Co,35%,10\n3,AMD,Advanced Micro Devices,25%,10\n",
+    ).unwrap();
+
+    let run = |match_mode: &str| {
+        let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+        cmd.arg("-d")
+            .arg(temp_dir.path())
+            .arg("-f")
+            .arg("search")
+            .arg("--query")
+            .arg("semiconductor")
+            .arg("--query")
+            .arg("chip")
+            .arg("--match")
+            .arg(match_mode)
+            .assert()
+            .success()
+    };
+
+    run("any").stdout(predicate::str::contains("Found 2 matching holdings"));
+    run("all")
+        .stdout(predicate::str::contains("Found 1 matching holdings"))
+        .stdout(predicate::str::contains("TSM"));
+}
 
+#[test]
+fn test_portfolio_state_json() {
     let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
-    cmd.arg("-d")
+    let output = cmd.arg("-d")
         .arg("./example-data")
         .arg("--etfs")
         .arg("IVW,IWF")
         .arg("-f")
-        .arg("summary")
-        .arg("-o")
-        .arg(&output_path)
-        .assert()
-        .success()
-        .stdout(predicate::str::contains("Total ETFs: 2"));
-
-    // Verify the file was created
-    assert!(output_path.exists());
-
-    // Verify content contains exactly 2 ETFs (+ header)
-    let content = fs::read_to_string(&output_path).unwrap();
-    let lines: Vec<&str> = content.lines().collect();
-    assert_eq!(lines.len(), 3); // Header + 2 ETFs
+        .arg("portfolio-state")
+        .arg("--format")
+        .arg("json")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let state: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(state["etfs"], serde_json::json!(["IVW", "IWF"]));
+    assert!(state["rows"].as_u64().unwrap() > 0);
 }
 
 #[test]
-fn test_compare_function() {
-    let temp_dir = TempDir::new().unwrap();
-    let output_path = temp_dir.path().join("test_compare.csv");
-
+fn test_largest_function() {
     let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
     cmd.arg("-d")
         .arg("./example-data")
         .arg("--etfs")
         .arg("IVW,IWF")
         .arg("-f")
-        .arg("compare")
-        .arg("-o")
-        .arg(&output_path)
+        .arg("largest")
         .assert()
         .success()
-        .stdout(predicate::str::contains("Comparing 2 ETFs"));
-
-    // Verify the file was created
-    assert!(output_path.exists());
-
-    // Verify column structure: Symbol, IVW, IWF
-    let content = fs::read_to_string(&output_path).unwrap();
-    let first_line = content.lines().next().unwrap();
-    assert_eq!(first_line, "Symbol,IVW,IWF");
+        .stdout(predicate::str::contains("Largest holding by ETF:"))
+        .stdout(predicate::str::contains("IVW:"))
+        .stdout(predicate::str::contains("IWF:"));
 }
 
 #[test]
-fn test_compare_function_default_extension() {
-    let temp_dir = TempDir::new().unwrap();
-    let output_path = temp_dir.path().join("test_compare");
-
+fn test_outliers_function() {
     let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
     cmd.arg("-d")
         .arg("./example-data")
-        .arg("--etfs")
-        .arg("IVW,IWF")
         .arg("-f")
-        .arg("compare")
-        .arg("-o")
-        .arg(&output_path)
+        .arg("outliers")
+        .arg("--z")
+        .arg("3")
         .assert()
-        .success();
-
-    // Verify the file was created with .csv extension
-    let csv_path = temp_dir.path().join("test_compare.csv");
-    assert!(csv_path.exists());
+        .success()
+        .stdout(predicate::str::contains("weight outliers (z-score > 3)"));
 }
 
 #[test]
-fn test_compare_function_requires_etfs() {
-    let temp_dir = TempDir::new().unwrap();
-    let output_path = temp_dir.path().join("test_compare.csv");
-
+fn test_overlap_vs_function() {
     let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
     cmd.arg("-d")
         .arg("./example-data")
         .arg("-f")
-        .arg("compare")
-        .arg("-o")
-        .arg(&output_path)
+        .arg("overlap-vs")
+        .arg("--benchmark")
+        .arg("iwf")
         .assert()
-        .failure()
-        .stderr(predicate::str::contains("Compare function requires --etfs to be specified"));
+        .success()
+        .stdout(predicate::str::contains("Weighted overlap vs IWF:"))
+        .stdout(predicate::str::contains("  IVW:"));
 }
 
 #[test]
-fn test_compare_function_requires_output() {
+fn test_overlap_vs_function_unknown_benchmark() {
     let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
     cmd.arg("-d")
         .arg("./example-data")
-        .arg("--etfs")
-        .arg("IVW,IWF")
         .arg("-f")
-        .arg("compare")
+        .arg("overlap-vs")
+        .arg("--benchmark")
+        .arg("VTI")
         .assert()
         .failure()
-        .stderr(predicate::str::contains("Compare function requires --output (-o) to be specified"));
+        .stderr(predicate::str::contains("Benchmark ETF not found: VTI"));
 }
 
 #[test]
@@ -1060,3 +2546,55 @@ fn test_config_file_force_option() {
     assert!(content.contains("ETF,Symbol,Name,Weight"));
 }
 
+#[test]
+fn test_validate_config_reports_missing_data_dir() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let config_file = temp_dir.path().join(".etf_analyzer.toml");
+    fs::write(&config_file, "data_dir = \"./no_such_dir\"\nsort_by = \"count\"\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .arg("-f")
+        .arg("validate-config")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("[FAIL] data_dir: directory does not exist: ./no_such_dir"))
+        .stdout(predicate::str::contains("[PASS] sort_by"))
+        .stderr(predicate::str::contains("Configuration has 1 issue(s)"));
+}
+
+#[test]
+fn test_validate_config_as_default_function() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let config_file = temp_dir.path().join(".etf_analyzer.toml");
+    fs::write(&config_file, "function = \"validate-config\"\n").unwrap();
+
+    // Runs the validation (which flags the default) instead of failing after loading data
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("[FAIL] function: validate-config cannot be the default function"))
+        .stderr(predicate::str::contains("Configuration has 1 issue(s)"));
+}
+
+#[test]
+fn test_validate_config_passes() {
+    let temp_dir = TempDir::new().unwrap();
+    let data_dir = temp_dir.path().join("test_data");
+    fs::create_dir(&data_dir).unwrap();
+
+    let config_file = temp_dir.path().join(".etf_analyzer.toml");
+    fs::write(&config_file, format!("data_dir = \"{}\"\nfunction = \"assets\"\n", data_dir.display())).unwrap();
+
+    let mut cmd = Command::cargo_bin("etf_analyzer").unwrap();
+    cmd.current_dir(temp_dir.path())
+        .arg("-f")
+        .arg("validate-config")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[PASS] function: known function: assets"))
+        .stdout(predicate::str::contains("Configuration is valid"));
+}